@@ -1,15 +1,15 @@
 use std::time::Duration;
-use open_dp100::{OpenDP100, OutputState};
+use open_dp100::{OpenDP100, Backend, OutputState};
 
 
 fn main() {
 
-    let count = OpenDP100::device_count().expect("Get Device failed");
+    let count = Backend::default().device_count().expect("Get Device failed");
     if count == 0{
         println!("Device Not found");
         return ;
     }
-    let api = OpenDP100::new(0).unwrap();
+    let api = OpenDP100::open(Backend::default(), 0).unwrap();
     println!("{:?}",api.sys_info().unwrap());
 
     println!("{:?}",api.device_info().unwrap());
@@ -1,7 +1,8 @@
 use rusb::{Device, Direction, TransferType};
 
+use open_dp100::{PID, VID};
 
-pub fn print_device_info(device: &Device<rusb::GlobalContext>) {
+fn print_device_info(device: &Device<rusb::GlobalContext>) {
     let device_desc = match device.device_descriptor() {
         Ok(desc) => desc,
         Err(_) => return,
@@ -50,3 +51,21 @@ pub fn print_device_info(device: &Device<rusb::GlobalContext>) {
         }
     }
 }
+
+fn main() {
+    let devices = rusb::devices().expect("listing usb devices failed");
+    let mut found = 0;
+    for device in devices.iter() {
+        let is_dp100 = match device.device_descriptor() {
+            Ok(desc) => desc.vendor_id() == VID && desc.product_id() == PID,
+            Err(_) => false,
+        };
+        if is_dp100 {
+            print_device_info(&device);
+            found += 1;
+        }
+    }
+    if found == 0 {
+        println!("Device Not found");
+    }
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use open_dp100_protocol::{BasicSet, Operational};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(decoded) = BasicSet::from_data(data) {
+        assert_eq!(decoded.to_data().len(), data.len());
+    }
+});
@@ -0,0 +1,16 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use open_dp100_protocol::{deserialize_in_frame, deserialize_out_frame, Frame, MAX_DATA_LEN};
+
+fuzz_target!(|data: &[u8]| {
+    let mut report = [0u8; 64];
+    let n = data.len().min(64);
+    report[..n].copy_from_slice(&data[..n]);
+
+    let mut frame = Frame::empty();
+    if deserialize_in_frame(&report, &mut frame).is_ok() {
+        assert!(frame.data().len() <= MAX_DATA_LEN);
+    }
+    let _ = deserialize_out_frame(&report, &mut frame);
+});
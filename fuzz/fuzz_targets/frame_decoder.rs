@@ -0,0 +1,19 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use open_dp100::FrameDecoder;
+
+// arbitrary stream, fed in arbitrary pieces: the first byte picks the piece size
+fuzz_target!(|data: &[u8]| {
+    let (piece, stream) = match data.split_first() {
+        Some((piece, stream)) => ((*piece as usize).max(1), stream),
+        None => return,
+    };
+
+    let mut decoder = FrameDecoder::new();
+    for chunk in stream.chunks(piece) {
+        decoder.push(chunk);
+        while decoder.next_frame().is_some() {}
+    }
+    assert!(decoder.pending() <= stream.len());
+});
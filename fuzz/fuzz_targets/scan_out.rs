@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use open_dp100_protocol::{ScanOut, Operational};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(decoded) = ScanOut::from_data(data) {
+        assert_eq!(decoded.to_data().len(), data.len());
+    }
+});
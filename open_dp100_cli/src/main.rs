@@ -0,0 +1,663 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fs::File;
+
+use clap::{ArgMatches, Command, arg, value_parser};
+use open_dp100::{OpenDP100, Backend, BasicInfo, BasicSet, OutputState, SystemInfo, Simulator, Transport, Recorder, SessionConfig};
+use open_dp100::{DeviceInfo, Frame, FrameError, OpCode, Operational, OperationResult, ScanOut, SerialOut, MAX_DATA_LEN, REPORT_SIZE};
+use open_dp100::{deserialize_in_frame, deserialize_out_frame, serialize_in_frame, serialize_out_frame};
+use open_dp100::{CentiWatts, DeciCelsius, Milliamps, Millivolts, OutMode, WorkState};
+use open_dp100::simulator::PRESET_COUNT;
+use open_dp100::usbmon::{self, UsbDirection};
+
+#[derive(Debug)]
+struct Config {
+    config: Option<u32>,
+    on: bool,
+    off: bool,
+    vout: Option<Millivolts>,
+    iout: Option<Milliamps>,
+    ovp: Option<Millivolts>,
+    ocp: Option<Milliamps>,
+}
+
+
+// Define a trait for printing the structures
+pub trait Printable {
+    fn print(&self);
+}
+
+// Implement the trait for each structure
+impl Printable for BasicInfo {
+    fn print(&self) {
+        println!("Basic Info:");
+        println!("  vin:{}", self.vin);
+        println!("  vout:{}", self.vout);
+        println!("  iout:{}", self.iout);
+        println!("  vo_max:{}", self.vo_max);
+        println!("  temp1:{}", self.temp1);
+        println!("  temp2:{}", self.temp2);
+        println!("  dc_5v:{}", self.dc_5v);
+        println!("  out_mode:{}", self.out_mode);
+        println!("  work_st:{}", self.work_st);
+        println!("  power:{}", self.power());
+        match self.load_resistance() {
+            Some(load) => println!("  load:{}", load),
+            None => println!("  load:-"), // no current flowing
+        }
+        println!("  dropout:{}", self.dropout());
+        println!("  headroom:{}", self.headroom());
+        println!("  dc_5v in spec:{}", if self.dc_5v_in_spec() { "yes" } else { "no" });
+        println!();
+    }
+}
+
+impl Printable for BasicSet {
+    fn print(&self) {
+        print!("Basic Set <{}>: ",self.index);
+        match self.state {
+            OutputState::On => println!("On"),
+            OutputState::Off => println!("Off"),
+            OutputState::Unknown(value) => println!("unknown ({})", value),
+        }
+
+        println!("  vo_set:{}", self.vo_set);
+        println!("  io_set:{}", self.io_set);
+        println!("  ovp_set:{}", self.ovp_set);
+        println!("  ocp_set:{}", self.ocp_set);
+        println!();
+    }
+}
+
+impl Printable for SystemInfo {
+    fn print(&self) {
+        println!("System Info:");
+        println!("  blk_lev:{}", self.blk_lev);
+        println!("  opp:{}", self.opp);
+        println!("  opt:{}", self.opt);
+        println!("  vol_kev:{}", self.vol_kev);
+        println!();
+    }
+}
+
+type Device = OpenDP100<Box<dyn Transport + Send>>;
+
+// limits accepted by `set`
+const MAX_VOLTAGE: Millivolts = Millivolts(36000);
+const MAX_CURRENT: Milliamps = Milliamps(36000);
+
+fn device_count(simulate:bool,backend:Backend) -> usize {
+    if simulate {
+        return 1;
+    }
+    backend.device_count().unwrap()
+}
+
+fn configured(mut device:Device,config:&SessionConfig) -> Device {
+    device.set_config(config.clone());
+    device
+}
+
+fn open_device(simulate:bool,backend:Backend,device_idx:usize,config:&SessionConfig) -> Option<Device> {
+    if simulate {
+        if device_idx != 0 {
+            return None;
+        }
+        return Some(configured(OpenDP100::with_transport(Box::new(Simulator::new())), config));
+    }
+    OpenDP100::open(backend, device_idx).ok().map(|device| configured(device, config))
+}
+
+// `--serial` and `--path` win over `--device`
+fn select_device(simulate:bool,backend:Backend,matches:&ArgMatches,config:&SessionConfig) -> Option<Device> {
+    if !simulate {
+        if let Some(serial) = matches.get_one::<String>("serial") {
+            return backend.open_by_serial(serial).ok().map(|device| configured(device, config));
+        }
+        if let Some(path) = matches.get_one::<String>("path") {
+            return backend.open_by_path(path).ok().map(|device| configured(device, config));
+        }
+    }
+    let device_index:u8 = *matches.get_one("device").expect("device setting failed");
+    open_device(simulate, backend, device_index as usize, config)
+}
+
+// how `select_device` picked the device, for the status header
+fn selector(simulate:bool,matches:&ArgMatches) -> String {
+    if !simulate {
+        if let Some(serial) = matches.get_one::<String>("serial") {
+            return format!("serial:{}", serial);
+        }
+        if let Some(path) = matches.get_one::<String>("path") {
+            return format!("path:{}", path);
+        }
+    }
+    let device_index:u8 = *matches.get_one("device").expect("device setting failed");
+    device_index.to_string()
+}
+
+// wrap the device in a Recorder if `--record <FILE>` is given
+fn record_device(device:Device,record:Option<&String>) -> Device {
+    match record {
+        Some(path) => {
+            let config = device.config().clone();
+            let recorder = Recorder::create(device.into_transport(), path).expect("create capture file failed");
+            configured(OpenDP100::with_transport(Box::new(recorder)), &config)
+        }
+        None => device
+    }
+}
+
+fn hex(data:&[u8]) -> String {
+    data.iter().map(|d| format!("{:02x}", d)).collect()
+}
+
+fn parse_hex(text:&str) -> Result<Vec<u8>,String> {
+    let digits:String = text.trim_start_matches("0x").chars().filter(|c| !c.is_whitespace() && *c != ':' && *c != '-').collect();
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!("odd number of hex digits in {}", text));
+    }
+    (0..digits.len()).step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).map_err(|_| format!("invalid hex in {}", text)))
+        .collect()
+}
+
+// decimal, or hex with 0x
+fn parse_number(text:&str) -> Result<i64,String> {
+    let res = match text.strip_prefix("0x") {
+        Some(digits) => i64::from_str_radix(digits, 16),
+        None => text.parse()
+    };
+    res.map_err(|_| format!("invalid number {}", text))
+}
+
+/// Print one frame with direction, op code, crc status and decoded fields
+fn print_frame(prefix:&str, data:&[u8]) {
+    if data.len() > REPORT_SIZE {
+        println!("{}longer than a report: {}", prefix, hex(data));
+        return;
+    }
+    // a cut off frame would only show up as a crc mismatch, header to crc is len + 6 bytes
+    let need = match data.get(3) {
+        Some(&len) if len as usize > MAX_DATA_LEN => 0,
+        Some(&len) => len as usize + 6,
+        None => 6
+    };
+    if data.len() < need {
+        println!("{}truncated (have {}, need {}): {}", prefix, data.len(), need, hex(data));
+        return;
+    }
+    let mut report = [0u8;REPORT_SIZE];
+    report[..data.len()].copy_from_slice(data);
+    let mut frame = Frame::empty();
+    let (direction, res) = match report[0] {
+        0xfb => ("host->device", deserialize_out_frame(&report, &mut frame)),
+        _ => ("device->host", deserialize_in_frame(&report, &mut frame)),
+    };
+    match res {
+        Ok(()) => println!("{}{} {:?} serial:{} len:{} crc:ok", prefix, direction, frame.op_code, frame.serial_num, frame.data().len()),
+        Err(FrameError::InvalidCrc) => {
+            println!("{}{} {:?} serial:{} crc:BAD {}", prefix, direction, frame.op_code, frame.serial_num, hex(data));
+            return;
+        }
+        Err(e) => {
+            println!("{}{}: {}", prefix, e, hex(data));
+            return;
+        }
+    }
+
+    let payload = frame.data();
+    if payload.is_empty() {
+        return;
+    }
+    match decode_payload(frame.op_code, report[0] == 0xfa, payload) {
+        Some(fields) => println!("{}  {}", prefix, fields),
+        None => println!("{}  data:{}", prefix, hex(payload)),
+    }
+}
+
+fn decode_payload(op_code:OpCode, from_device:bool, payload:&[u8]) -> Option<String> {
+    fn show<T:std::fmt::Debug>(res:Result<T,FrameError>) -> Option<String> {
+        res.ok().map(|v| format!("{:?}", v))
+    }
+    match (op_code, from_device, payload.len()) {
+        (OpCode::DeviceInfo, true, _) => show(DeviceInfo::from_data(payload)),
+        (OpCode::BasicInfo, true, _) => show(BasicInfo::from_data(payload)),
+        (OpCode::SystemInfo, _, _) => show(SystemInfo::from_data(payload)),
+        (OpCode::BasicSet, false, 1) => Some(format!("query index:0x{:02x}", payload[0])),
+        (OpCode::BasicSet, true, 1) => show(OperationResult::from_data(payload)),
+        (OpCode::BasicSet, _, 10) => show(BasicSet::from_data(payload)),
+        (OpCode::ScanOut, _, _) => show(ScanOut::from_data(payload)),
+        (OpCode::SerialOut, _, _) => show(SerialOut::from_data(payload)),
+        _ => None
+    }
+}
+
+// `name=value` pairs given to `encode`, each is taken once
+struct Fields(HashMap<String,String>);
+
+impl Fields {
+    fn parse(pairs:&[&String]) -> Result<Self,String> {
+        let mut fields = HashMap::new();
+        for pair in pairs {
+            let (name, value) = pair.split_once('=').ok_or_else(|| format!("expected name=value, got {}", pair))?;
+            fields.insert(name.to_string(), value.to_string());
+        }
+        Ok(Fields(fields))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn text(&mut self, name:&str) -> Option<String> {
+        self.0.remove(name)
+    }
+
+    // missing fields are 0
+    fn number<T:TryFrom<i64>>(&mut self, name:&str) -> Result<T,String> {
+        let value = match self.0.remove(name) {
+            Some(text) => parse_number(&text)?,
+            None => 0
+        };
+        T::try_from(value).map_err(|_| format!("{} out of range", name))
+    }
+
+    fn finish(self) -> Result<(),String> {
+        match self.0.keys().next() {
+            Some(name) => Err(format!("unknown field {}", name)),
+            None => Ok(())
+        }
+    }
+}
+
+fn parse_op_code(name:&str) -> Result<OpCode,String> {
+    match name {
+        "device-info" => Ok(OpCode::DeviceInfo),
+        "basic-info" => Ok(OpCode::BasicInfo),
+        "basic-set" => Ok(OpCode::BasicSet),
+        "system-info" => Ok(OpCode::SystemInfo),
+        "scan-out" => Ok(OpCode::ScanOut),
+        "serial-out" => Ok(OpCode::SerialOut),
+        _ => {
+            let number = u8::try_from(parse_number(name)?).map_err(|_| format!("op code {} out of range", name))?;
+            OpCode::try_from(number).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// Payload for `op_code` built from field values, empty when no field is given
+fn encode_payload(op_code:OpCode, reply:bool, mut fields:Fields) -> Result<Vec<u8>,String> {
+    if let Some(data) = fields.text("data") {
+        fields.finish()?;
+        return parse_hex(&data);
+    }
+    if fields.is_empty() {
+        return Ok(Vec::new());
+    }
+    let data = match op_code {
+        OpCode::DeviceInfo => {
+            let mut dev_type = [0u8;16];
+            let name = fields.text("dev_type").unwrap_or_default();
+            let n = name.len().min(dev_type.len());
+            dev_type[..n].copy_from_slice(&name.as_bytes()[..n]);
+            let mut dev_sn = [0u8;12];
+            let sn = parse_hex(&fields.text("dev_sn").unwrap_or_default())?;
+            let n = sn.len().min(dev_sn.len());
+            dev_sn[..n].copy_from_slice(&sn[..n]);
+            DeviceInfo {
+                dev_type,
+                hdw_ver: fields.number("hdw_ver")?,
+                app_ver: fields.number("app_ver")?,
+                boot_ver: fields.number("boot_ver")?,
+                run_area: fields.number("run_area")?,
+                dev_sn,
+                year: fields.number("year")?,
+                moon: fields.number("moon")?,
+                day: fields.number("day")?,
+            }.to_data().to_vec()
+        }
+        OpCode::BasicInfo => BasicInfo {
+            vin: Millivolts(fields.number("vin")?),
+            vout: Millivolts(fields.number("vout")?),
+            iout: Milliamps(fields.number("iout")?),
+            vo_max: Millivolts(fields.number("vo_max")?),
+            temp1: DeciCelsius(fields.number("temp1")?),
+            temp2: DeciCelsius(fields.number("temp2")?),
+            dc_5v: Millivolts(fields.number("dc_5v")?),
+            out_mode: OutMode::from(fields.number::<u8>("out_mode")?),
+            work_st: WorkState::from(fields.number::<u8>("work_st")?),
+        }.to_data().to_vec(),
+        OpCode::SystemInfo => SystemInfo {
+            blk_lev: fields.number("blk_lev")?,
+            opp: CentiWatts(fields.number("opp")?),
+            opt: DeciCelsius(fields.number("opt")?),
+            vol_kev: fields.number("vol_kev")?,
+        }.to_data().to_vec(),
+        OpCode::BasicSet if reply && fields.0.contains_key("result") => {
+            vec![fields.number("result")?]
+        }
+        OpCode::BasicSet if fields.0.len() == 1 && fields.0.contains_key("index") => {
+            vec![fields.number("index")?]
+        }
+        OpCode::BasicSet => BasicSet {
+            index: fields.number("index")?,
+            state: OutputState::from(fields.number::<u8>("state")?),
+            vo_set: Millivolts(fields.number("vo_set")?),
+            io_set: Milliamps(fields.number("io_set")?),
+            ovp_set: Millivolts(fields.number("ovp_set")?),
+            ocp_set: Milliamps(fields.number("ocp_set")?),
+        }.to_data().to_vec(),
+        OpCode::ScanOut => ScanOut {
+            on_off: fields.number("on_off")?,
+            on_time: fields.number("on_time")?,
+            out_val: fields.number("out_val")?,
+            scan_mode: fields.number("scan_mode")?,
+            start: fields.number("start")?,
+            end: fields.number("end")?,
+            step: fields.number("step")?,
+        }.to_data().to_vec(),
+        OpCode::SerialOut => SerialOut {
+            on_off: fields.number("on_off")?,
+            on_time: fields.number("on_time")?,
+            ser_start: fields.number("ser_start")?,
+            ser_end: fields.number("ser_end")?,
+            ser_vi: fields.number("ser_vi")?,
+            ser_vo: fields.number("ser_vo")?,
+            cycle_times: fields.number("cycle_times")?,
+        }.to_data().to_vec(),
+        OpCode::None => return Err("op code 0 has no fields, use data=<hex>".to_string()),
+    };
+    fields.finish()?;
+    Ok(data)
+}
+
+fn main() {
+    let matches = Command::new("dp100")
+        .version("1.0")
+        .author("lessu")
+        .about("dp100 cli program")
+        .arg(
+            arg!(simulate: --simulate "talk to a simulated DP100 instead of real hardware").global(true)
+        )
+        .arg(
+            arg!(backend: --backend <BACKEND> "usb driver to use: hidapi, hidraw or libusb, depending on the features built in").global(true)
+        )
+        .arg(
+            arg!(strict: --"strict-serial" "only accept replies carrying the request serial, for units known to echo it").global(true)
+        )
+        .subcommand(
+            Command::new("ls")
+                .about("print devices that avaliable")
+        )
+        .subcommand(
+            Command::new("status")
+                .about("print device status")
+                .args(&[
+                    arg!(device: -d --device <DEVICE> "select current device").value_parser(value_parser!(u8)).default_value("0"),
+                    arg!(serial: --serial <SERIAL> "select the device with this usb serial number"),
+                    arg!(path: --path <PATH> "select the device at this path, as printed by ls"),
+                    arg!(allconfig: -a --"all-config" "print all config,if -a is not set,only the config current in use is printed"),
+                    arg!(system: -s --system "print system info(eg backlight level...)"),
+                    arg!(record: --record <FILE> "record all usb traffic to FILE")
+                ]),
+        )
+        .subcommand(
+            Command::new("set")
+                .about("set value ")
+                .arg(
+                    arg!(device: -d --device <DEVICE> "select current device").value_parser(value_parser!(u8)).default_value("0"),
+                )
+                .arg(
+                    arg!(serial: --serial <SERIAL> "select the device with this usb serial number")
+                )
+                .arg(
+                    arg!(path: --path <PATH> "select the device at this path, as printed by ls")
+                )
+                .arg(
+                    arg!(record: --record <FILE> "record all usb traffic to FILE")
+                )
+                .arg(
+                    arg!([keyvalue] ...  "config=<index>:switch to config before set setting,range 0~9\n\
+                                          on:set output on\n\
+                                          off:set output off\n\
+                                          v=<volt>:set vout,range 0.00~36.00\n\
+                                          i=<current>: set iout,range 0.00~10.00\n\
+                                          ov=<volt>: set ovp,range 0.00~36.00\n\
+                                          oc=<current>: set ocp,range 0.00~10.00")
+                )
+                .after_help("example:\n\
+                                dp100 set on\n\
+                                dp100 set config=2\n\
+                                dp100 set config=2 on vout=13.4\n\
+                            ")
+        )
+        .subcommand(
+            Command::new("decode")
+                .about("decode DP100 frames from hex or from a usbmon capture")
+                .args(&[
+                    arg!(pcap: --pcap <FILE> "usbmon capture in pcap or pcapng format, eg. from `tcpdump -i usbmon1 -w FILE`"),
+                    arg!([hex] ... "frames in hex, one per argument"),
+                ])
+                .after_help("example:\n\
+                                dp100 decode fb300100309f\n\
+                                dp100 decode --pcap bench.pcapng\n\
+                            ")
+        )
+        .subcommand(
+            Command::new("encode")
+                .about("build a frame from field values and print it in hex")
+                .args(&[
+                    arg!(<opcode> "device-info, basic-info, basic-set, system-info, scan-out, serial-out or a number"),
+                    arg!(reply: --reply "build a device->host frame instead of a request"),
+                    arg!(serial: --serial <SERIAL> "frame serial number").value_parser(value_parser!(u8)).default_value("0"),
+                    arg!([fields] ... "field=value, names as in the decode output, data=<hex> gives the raw payload"),
+                ])
+                .after_help("example:\n\
+                                dp100 encode basic-info\n\
+                                dp100 encode basic-set index=0x21 state=1 vo_set=5000 io_set=1000 ovp_set=30500 ocp_set=5050\n\
+                                dp100 encode --reply basic-set result=1\n\
+                            ")
+        )
+        .get_matches();
+
+    let simulate = matches.get_flag("simulate");
+    let backend:Backend = match matches.get_one::<String>("backend") {
+        Some(name) => name.parse().expect("backend not available"),
+        None => Backend::default()
+    };
+    let session = SessionConfig {
+        accept_unnumbered: !matches.get_flag("strict"),
+        ..SessionConfig::default()
+    };
+
+    match matches.subcommand() {
+        Some(("ls", _ls_matches)) => {
+            // Device count:%d
+            //    0 <name> sn:<sn> hdw_ver:<hdw_ver> app_ver:<app_ver> <YYYY-mm-dd>
+            //    1 <name> sn:<sn> hdw_ver:<hdw_ver> app_ver:<app_ver> <YYYY-mm-dd>
+            //        path:<path> usb_serial:<usb serial>
+            let count = device_count(simulate, backend);
+            let descriptors = if simulate { Vec::new() } else { backend.enumerate().unwrap() };
+            println!("Device count: {}", count);
+            for i in 0..count {
+                let device = open_device(simulate, backend, i, &session).unwrap();
+                let info = device.device_info().unwrap();
+                let date = match info.manufactured_on() {
+                    Some(date) => date.to_string(),
+                    None => "-".to_string(),
+                };
+                println!(
+                    "{} {} sn:{} hdw_ver:{} app_ver:{} {}",
+                    i + 1,
+                    info.model(),
+                    info.serial(),
+                    info.hardware_version(),
+                    info.firmware_version(),
+                    date
+                );
+                if let Some(descriptor) = descriptors.get(i) {
+                    println!("    path:{} usb_serial:{}", descriptor.path, descriptor.serial.as_deref().unwrap_or("-"));
+                }
+            }
+
+        }
+        Some(("status", status_matches)) => {
+            let device = select_device(simulate, backend, status_matches, &session).expect("open device failed");
+            let device = record_device(device, status_matches.get_one("record"));
+            
+            let info = device.device_info().unwrap();
+
+            println!("Device {} name:{}",selector(simulate, status_matches),info.model());
+            device.basic_info().unwrap().print();
+            
+            if status_matches.get_flag("system") {
+                device.sys_info().unwrap().print();
+            }
+
+            let current_config = device.current_basic_set().unwrap();
+            if status_matches.get_flag("allconfig") {
+                for i in 0..PRESET_COUNT{
+                    if current_config.index as usize == i{
+                        print!("[*] ",);
+                    }else{
+                        print!("[ ] ",);
+                    }
+                    device.basic_set(i).unwrap().print();
+                }
+            } else {
+                current_config.print();
+            }
+
+
+        }
+        Some(("set", set_matches)) => {
+            let mut config = Config {
+              config: None,
+              on: false,
+              off: false,
+              vout: None,
+              iout: None,
+              ovp: None,
+              ocp: None,
+            };
+            let device = select_device(simulate, backend, set_matches, &session).expect("open device failed");
+            let device = record_device(device, set_matches.get_one("record"));
+    
+            let keyvalues:Vec<&String> = set_matches.get_many("keyvalue")
+                .expect("at least on param should be set")
+                .collect();
+
+            for keyvalue in keyvalues.iter() {
+              let kv: Vec<&str> = keyvalue.split("=").collect();
+              match kv[0] {
+                  "config" => {
+                      let index = kv[1].parse::<u32>().unwrap();
+                      if index > 9 {
+                          panic!("config index out of range");
+                      }
+                      config.config = Some(index);
+                  },
+                  "on" => config.on = true,
+                  "off" => config.off = true,
+                  "v" => {
+                      let volt = Millivolts::from_volts(kv[1].parse::<f64>().unwrap())
+                          .filter(|&v| v <= MAX_VOLTAGE)
+                          .expect("vout out of range");
+                      config.vout = Some(volt);
+                  },
+                  "i" => {
+                      let current = Milliamps::from_amps(kv[1].parse::<f64>().unwrap())
+                          .filter(|&c| c <= MAX_CURRENT)
+                          .expect("iout out of range");
+                      config.iout = Some(current);
+                  },
+                  "ov" => {
+                      let volt = Millivolts::from_volts(kv[1].parse::<f64>().unwrap())
+                          .filter(|&v| v <= MAX_VOLTAGE)
+                          .expect("ovp out of range");
+                      config.ovp = Some(volt);
+                  },
+                  "oc" => {
+                      let current = Milliamps::from_amps(kv[1].parse::<f64>().unwrap())
+                          .filter(|&c| c <= MAX_CURRENT)
+                          .expect("ocp out of range");
+                      config.ocp = Some(current);
+                  },
+                  _ => panic!("Invalid key-value pair"),
+              }
+            }
+
+            if let Some(idx) = config.config{
+                let current_set = device.current_basic_set().unwrap();
+                if current_set.index != idx as u8 {
+                    device.switch_config(idx as usize).unwrap();
+                }
+            }
+            let mut current_set = device.current_basic_set().unwrap();
+
+            if config.on {
+                current_set.state = OutputState::On;
+            }
+            if config.off {
+                current_set.state = OutputState::Off;
+            }
+            if let Some(vout) = config.vout{
+                current_set.vo_set = vout;
+            }
+            if let Some(iout) = config.iout{
+                current_set.io_set = iout;
+            }
+            if let Some(ovp) = config.ovp{
+                current_set.ovp_set = ovp;
+            }
+            if let Some(ocp) = config.ocp{
+                current_set.ocp_set = ocp;
+            }
+            device.update_basic_set(&current_set, false).unwrap();
+
+        }
+        Some(("decode", decode_matches)) => {
+            if let Some(path) = decode_matches.get_one::<String>("pcap") {
+                let file = File::open(path).expect("open capture failed");
+                let packets = usbmon::read_packets(file).expect("read capture failed");
+                let reports = usbmon::dp100_reports(&packets);
+                println!("{} DP100 reports in {} usb packets", reports.len(), packets.len());
+                for report in reports.iter() {
+                    let dir = match report.direction {
+                        UsbDirection::Out => "OUT",
+                        UsbDirection::In => "IN ",
+                    };
+                    let prefix = format!("{:.6} {} {}:{} ", report.timestamp, dir, report.bus, report.device);
+                    print_frame(&prefix, &report.data);
+                }
+            }
+            if let Some(frames) = decode_matches.get_many::<String>("hex") {
+                for text in frames {
+                    let data = parse_hex(text).expect("invalid hex");
+                    print_frame("", &data);
+                }
+            }
+        }
+        Some(("encode", encode_matches)) => {
+            let op_code = parse_op_code(encode_matches.get_one::<String>("opcode").unwrap()).expect("invalid op code");
+            let reply = encode_matches.get_flag("reply");
+            let pairs:Vec<&String> = encode_matches.get_many("fields").map(|v| v.collect()).unwrap_or_default();
+            let fields = Fields::parse(&pairs).expect("invalid field");
+            let data = encode_payload(op_code, reply, fields).expect("invalid field");
+            let mut frame = Frame::new(op_code, &data)
+                .unwrap_or_else(|_| panic!("payload longer than {} bytes", MAX_DATA_LEN));
+            frame.serial_num = *encode_matches.get_one("serial").unwrap();
+            let mut report = [0u8;REPORT_SIZE];
+            if reply {
+                serialize_in_frame(&frame, &mut report);
+            } else {
+                serialize_out_frame(&frame, &mut report);
+            }
+            let frame_bytes = &report[0..data.len() + 6];
+            println!("{}", hex(frame_bytes));
+            print_frame("  ", frame_bytes);
+        }
+        _ => unreachable!(),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,71 @@
+use std::process::{Command, Output};
+
+fn cli(args:&[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cli")).args(args).output().expect("run cli failed")
+}
+
+fn stdout(args:&[&str]) -> String {
+    let output = cli(args);
+    assert!(output.status.success(), "cli {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn decode_request() {
+    assert_eq!(stdout(&["decode", "fb300100309f"]), "host->device BasicInfo serial:1 len:0 crc:ok\n");
+}
+
+#[test]
+fn decode_reply_fields() {
+    let out = stdout(&["decode", "fa4000060404292003027ac4"]);
+    let mut lines = out.lines();
+    assert_eq!(lines.next(), Some("device->host SystemInfo serial:0 len:6 crc:ok"));
+    assert!(lines.next().unwrap().contains("blk_lev: 4"));
+}
+
+#[test]
+fn decode_bad_crc() {
+    assert_eq!(stdout(&["decode", "fb300100309e"]), "host->device BasicInfo serial:1 crc:BAD fb300100309e\n");
+}
+
+#[test]
+fn decode_broken_frames() {
+    let out = stdout(&["decode", "fc0000000000", "fb9900000000", "fa40003b0000"]);
+    assert_eq!(out.lines().collect::<Vec<_>>(), [
+        "unknown frame header: fc0000000000",
+        "unknown op code: fb9900000000",
+        "data length exceeds a report: fa40003b0000",
+    ]);
+}
+
+#[test]
+fn decode_truncated_frames() {
+    // a usbmon capture cut short must not look like a crc error
+    let out = stdout(&["decode", "fb30", "fa4000060404292003", "fa4000060404292003027a"]);
+    assert_eq!(out.lines().collect::<Vec<_>>(), [
+        "truncated (have 2, need 6): fb30",
+        "truncated (have 9, need 12): fa4000060404292003",
+        "truncated (have 11, need 12): fa4000060404292003027a",
+    ]);
+}
+
+#[test]
+fn encode_request() {
+    assert_eq!(stdout(&["encode", "basic-set", "index=0x80"]), "fb35000180ce28\n  host->device BasicSet serial:0 len:1 crc:ok\n    query index:0x80\n");
+}
+
+#[test]
+fn encoded_reply_decodes() {
+    let out = stdout(&["encode", "--reply", "--serial", "9", "basic-set", "result=1"]);
+    let frame = out.lines().next().unwrap();
+    assert_eq!(stdout(&["decode", frame]), out.lines().skip(1).map(|line| format!("{}\n", &line[2..])).collect::<String>());
+    assert!(out.contains("device->host BasicSet serial:9 len:1 crc:ok"));
+}
+
+#[test]
+fn encode_rejects_bad_input() {
+    // 0x110 must not wrap around to 0x10
+    assert!(!cli(&["encode", "0x110"]).status.success());
+    assert!(!cli(&["encode", "basic-info", "vin=70000"]).status.success());
+    assert!(!cli(&["encode", "basic-info", "nope=1"]).status.success());
+}
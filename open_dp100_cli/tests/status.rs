@@ -0,0 +1,25 @@
+use std::process::Command;
+
+fn status(args:&[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_cli")).arg("--simulate").arg("status").args(args).output().expect("run cli failed");
+    assert!(output.status.success(), "cli status {:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn header_names_the_device_index() {
+    assert!(status(&[]).starts_with("Device 0 name:DP100\n"));
+}
+
+#[test]
+fn current_preset_is_printed() {
+    assert!(status(&[]).contains("Basic Set <0>: Off"));
+}
+
+#[test]
+fn all_ten_presets_are_listed() {
+    let out = status(&["-a"]);
+    assert_eq!(out.matches("Basic Set <").count(), 10);
+    assert!(out.contains("[*] Basic Set <0>"));
+    assert!(out.contains("[ ] Basic Set <9>"));
+}
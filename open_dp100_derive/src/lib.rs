@@ -0,0 +1,134 @@
+//! `#[derive(Operational)]` for open_dp100 payloads.
+//!
+//! The payload layout is the struct itself: fields are packed in
+//! declaration order without padding, each one takes `Field::SIZE` bytes.
+//! Fields wider than one byte need `#[le]` or `#[be]`, the DP100 itself
+//! only uses little endian.
+//!
+//! ```ignore
+//! #[derive(Operational)]
+//! pub struct SystemInfo {
+//!     pub blk_lev: i8,
+//!     #[le]
+//!     pub opp: u16,
+//!     #[le]
+//!     pub opt: u16,
+//!     pub vol_kev: i8,
+//! }
+//! ```
+//!
+//! generates `SystemInfo::SIZE` (6 here), `Operational<6>` with `to_data`,
+//! and a `from_data` that rejects payloads of any other length.
+//!
+//! The generated code names `::open_dp100_protocol`. A crate that only
+//! depends on `open_dp100`, which re-exports the same items, says so with
+//! `#[operational(crate = "open_dp100")]` on the struct.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr, Path};
+
+#[proc_macro_derive(Operational, attributes(operational, le, be))]
+pub fn derive_operational(input:TokenStream) -> TokenStream{
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input){
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into()
+    }
+}
+
+// `#[operational(crate = "...")]`, or the protocol crate
+fn crate_path(input:&DeriveInput) -> Result<Path,Error>{
+    let mut path:Path = syn::parse_quote!(::open_dp100_protocol);
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("operational")){
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate"){
+                let name:LitStr = meta.value()?.parse()?;
+                let name:Path = name.parse()?;
+                path = syn::parse_quote!(::#name);
+                Ok(())
+            }else{
+                Err(meta.error("expected `crate = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(path)
+}
+
+fn expand(input:&DeriveInput) -> Result<proc_macro2::TokenStream,Error>{
+    let name = &input.ident;
+    let krate = crate_path(input)?;
+    if !input.generics.params.is_empty(){
+        return Err(Error::new_spanned(&input.generics,"Operational payloads can not be generic"));
+    }
+    let fields = match &input.data{
+        Data::Struct(data) => match &data.fields{
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(&data.fields,"Operational needs named fields"))
+        },
+        _ => return Err(Error::new(Span::call_site(),"Operational can only be derived for structs"))
+    };
+
+    let mut names = Vec::new();
+    let mut types = Vec::new();
+    let mut big_endian = Vec::new();
+    let mut checks = Vec::new();
+    for field in fields{
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let le = field.attrs.iter().any(|a| a.path().is_ident("le"));
+        let be = field.attrs.iter().any(|a| a.path().is_ident("be"));
+        if le && be{
+            return Err(Error::new_spanned(field,"a field is either #[le] or #[be]"));
+        }
+        if !le && !be{
+            // a byte order is only optional where there is nothing to order
+            let msg = LitStr::new(&format!("`{}::{}` is wider than one byte, mark it #[le] or #[be]",name,ident),ident.span());
+            checks.push(quote!{
+                const _: () = assert!(!<#ty as #krate::Field>::ORDERED,#msg);
+            });
+        }
+        names.push(ident);
+        types.push(ty);
+        big_endian.push(be);
+    }
+
+    // locals are prefixed so they can not clash with field names
+    Ok(quote!{
+        impl #name{
+            /// Payload size in bytes
+            pub const SIZE:usize = 0 #(+ <#types as #krate::Field>::SIZE)*;
+        }
+
+        #(#checks)*
+
+        impl #krate::Operational<{ #name::SIZE }> for #name{
+            fn to_data(&self) -> [u8;#name::SIZE]{
+                let mut __data = [0u8;#name::SIZE];
+                let mut __offset = 0;
+                #(
+                    let __end = __offset + <#types as #krate::Field>::SIZE;
+                    #krate::Field::encode(&self.#names,&mut __data[__offset..__end],#big_endian);
+                    __offset = __end;
+                )*
+                let _ = __offset;
+                __data
+            }
+
+            fn from_data(__data:&[u8]) -> ::core::result::Result<Self,#krate::FrameError>{
+                if __data.len() != #name::SIZE{
+                    return Err(#krate::FrameError::InvalidPayload);
+                }
+                let mut __offset = 0;
+                #(
+                    let __end = __offset + <#types as #krate::Field>::SIZE;
+                    let #names = <#types as #krate::Field>::decode(&__data[__offset..__end],#big_endian)?;
+                    __offset = __end;
+                )*
+                let _ = __offset;
+                Ok(#name{ #(#names),* })
+            }
+        }
+    })
+}
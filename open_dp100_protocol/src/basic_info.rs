@@ -0,0 +1,42 @@
+use crate::data::BasicInfo;
+use crate::units::{Milliohms, Milliwatts, Millivolts};
+
+/// Lowest `dc_5v` still in spec, USB allows 5 V ± 5 %
+pub const DC_5V_MIN: Millivolts = Millivolts(4750);
+/// Highest `dc_5v` still in spec
+pub const DC_5V_MAX: Millivolts = Millivolts(5250);
+
+impl BasicInfo {
+    /// Output power, `vout` × `iout`
+    pub fn power(&self) -> Milliwatts {
+        // mV * mA = uW, rounded to mW. 65535 * 65535 + 500 still fits
+        let uw = self.vout.0 as u32 * self.iout.0 as u32;
+        Milliwatts((uw + 500) / 1000)
+    }
+
+    /// Resistance the output sees, `vout` / `iout`. `None` while no
+    /// current flows, eg. output off or nothing connected.
+    pub fn load_resistance(&self) -> Option<Milliohms> {
+        if self.iout.0 == 0 {
+            return None;
+        }
+        // mV * 1000 / mA = mOhm
+        let iout = self.iout.0 as u32;
+        Some(Milliohms((self.vout.0 as u32 * 1000 + iout / 2) / iout))
+    }
+
+    /// How far the output is below the input, `vin` - `vout`
+    pub fn dropout(&self) -> Millivolts {
+        Millivolts(self.vin.0.saturating_sub(self.vout.0))
+    }
+
+    /// How much higher the output could still be set, `vo_max` - `vout`
+    pub fn headroom(&self) -> Millivolts {
+        Millivolts(self.vo_max.0.saturating_sub(self.vout.0))
+    }
+
+    /// The 5 V auxiliary rail is within `DC_5V_MIN..=DC_5V_MAX`
+    pub fn dc_5v_in_spec(&self) -> bool {
+        (DC_5V_MIN..=DC_5V_MAX).contains(&self.dc_5v)
+    }
+}
@@ -0,0 +1,281 @@
+use core::fmt;
+
+use open_dp100_derive::Operational;
+
+use crate::units::{CentiWatts, DeciCelsius, Milliamps, Millivolts};
+
+// 0x10 DEVICE_INFO
+#[derive(Debug,Clone,PartialEq,Operational)]
+pub struct DeviceInfo {
+    pub dev_type: [u8; 16],
+    #[le]
+    pub hdw_ver: u16,
+    #[le]
+    pub app_ver: u16,
+    #[le]
+    pub boot_ver: u16,
+    #[le]
+    pub run_area: u16,
+    pub dev_sn: [u8; 12],
+    #[le]
+    pub year: u16,
+    pub moon: u8,
+    pub day: u8,
+}
+
+/// Output switch of a `BasicSet`, any other byte is kept as `Unknown`
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum OutputState{
+    On,
+    Off,
+    Unknown(u8),
+}
+
+impl From<u8> for OutputState {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => OutputState::Off,
+            0x01 => OutputState::On,
+            _ => OutputState::Unknown(value),
+        }
+    }
+}
+
+impl From<OutputState> for u8 {
+    fn from(value: OutputState) -> Self {
+        match value {
+            OutputState::Off => 0x00,
+            OutputState::On => 0x01,
+            OutputState::Unknown(value) => value,
+        }
+    }
+}
+
+// 0x35 BASIC_SET
+#[derive(Debug,Clone,PartialEq,Operational)]
+pub struct BasicSet {
+    pub index: u8,
+    pub state: OutputState,
+    #[le]
+    pub vo_set: Millivolts,
+    #[le]
+    pub io_set: Milliamps,
+    #[le]
+    pub ovp_set: Millivolts,
+    #[le]
+    pub ocp_set: Milliamps
+}
+
+// out_mode and work_st of BASIC_INFO, the vendor software does not name them.
+//
+// | value | out_mode | work_st |
+// |-------|----------|---------|
+// | 0     | off      | normal  |
+// | 1     | CV       | OVP     |
+// | 2     | CC       | OCP     |
+// | 3     |          | OPP     |
+//
+// Anything else, eg. over temperature or an input fault, is kept as
+// `Unknown` with the raw value until it has been seen in a capture.
+
+/// How the output is regulated right now.
+///
+/// The raw values are a guess that no capture confirms yet, anything
+/// else is kept as `Unknown` so nothing is lost.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum OutMode{
+    Off,
+    /// Constant voltage
+    Cv,
+    /// Constant current, the load draws more than `io_set`
+    Cc,
+    Unknown(u8),
+}
+
+impl From<u8> for OutMode {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => OutMode::Off,
+            1 => OutMode::Cv,
+            2 => OutMode::Cc,
+            _ => OutMode::Unknown(value),
+        }
+    }
+}
+
+impl From<OutMode> for u8 {
+    fn from(value: OutMode) -> Self {
+        match value {
+            OutMode::Off => 0,
+            OutMode::Cv => 1,
+            OutMode::Cc => 2,
+            OutMode::Unknown(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for OutMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OutMode::Off => f.write_str("off"),
+            OutMode::Cv => f.write_str("CV"),
+            OutMode::Cc => f.write_str("CC"),
+            OutMode::Unknown(value) => write!(f, "unknown ({})", value),
+        }
+    }
+}
+
+/// Protection state, anything but `Normal` means the output was switched off.
+///
+/// The raw values are a guess that no capture confirms yet. OTP and input
+/// fault have no known value and come out as `Unknown`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum WorkState{
+    Normal,
+    /// Over voltage protection tripped
+    Ovp,
+    /// Over current protection tripped
+    Ocp,
+    /// Over power protection tripped
+    Opp,
+    Unknown(u8),
+}
+
+impl From<u8> for WorkState {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => WorkState::Normal,
+            1 => WorkState::Ovp,
+            2 => WorkState::Ocp,
+            3 => WorkState::Opp,
+            _ => WorkState::Unknown(value),
+        }
+    }
+}
+
+impl From<WorkState> for u8 {
+    fn from(value: WorkState) -> Self {
+        match value {
+            WorkState::Normal => 0,
+            WorkState::Ovp => 1,
+            WorkState::Ocp => 2,
+            WorkState::Opp => 3,
+            WorkState::Unknown(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for WorkState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkState::Normal => f.write_str("normal"),
+            WorkState::Ovp => f.write_str("OVP"),
+            WorkState::Ocp => f.write_str("OCP"),
+            WorkState::Opp => f.write_str("OPP"),
+            WorkState::Unknown(value) => write!(f, "unknown ({})", value),
+        }
+    }
+}
+
+// 0x30 BASIC_INFO
+#[derive(Debug,Clone,PartialEq,Operational)]
+pub struct BasicInfo {
+    #[le]
+    pub vin: Millivolts,
+    #[le]
+    pub vout: Millivolts,
+    #[le]
+    pub iout: Milliamps,
+    #[le]
+    pub vo_max: Millivolts,
+    #[le]
+    pub temp1: DeciCelsius,
+    #[le]
+    pub temp2: DeciCelsius,
+    #[le]
+    pub dc_5v: Millivolts,
+    pub out_mode: OutMode,
+    pub work_st: WorkState,
+}
+
+
+// 0x40 SYSTEM_INFO
+#[derive(Debug,Clone,PartialEq,Operational)]
+pub struct SystemInfo {
+    pub blk_lev: i8,
+    /// over power protection
+    #[le]
+    pub opp: CentiWatts,
+    /// over temperature protection
+    #[le]
+    pub opt: DeciCelsius,
+    pub vol_kev: i8,
+}
+
+
+// 0x50 SCAN_OUT
+#[derive(Debug,Clone,PartialEq,Operational)]
+pub struct ScanOut {
+    pub on_off: u8,
+    #[le]
+    pub on_time: u16,
+    #[le]
+    pub out_val: u16,
+    pub scan_mode: u8,
+    #[le]
+    pub start: u16,
+    #[le]
+    pub end: u16,
+    #[le]
+    pub step: u16,
+}
+
+
+// 0x55 SERIAL_OUT
+
+#[derive(Debug,Clone,PartialEq,Operational)]
+pub struct SerialOut {
+    pub on_off: u8,
+    #[le]
+    pub on_time: u16,
+    pub ser_start: u8,
+    pub ser_end: u8,
+    #[le]
+    pub ser_vi: u16,
+    #[le]
+    pub ser_vo: u16,
+    pub cycle_times: u8,
+}
+
+/// Answer to a BASIC_SET update, any other byte is kept as `Unknown`
+#[derive(Debug,Clone,PartialEq)]
+pub enum OpResult{
+    Failed,
+    Success,
+    Unknown(u8),
+}
+
+impl From<u8> for OpResult {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => OpResult::Failed,
+            0x01 => OpResult::Success,
+            _ => OpResult::Unknown(value),
+        }
+    }
+}
+
+impl From<OpResult> for u8 {
+    fn from(value: OpResult) -> Self {
+        match value {
+            OpResult::Failed => 0x00,
+            OpResult::Success => 0x01,
+            OpResult::Unknown(value) => value,
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Operational)]
+pub struct OperationResult {
+    pub result: OpResult,
+}
\ No newline at end of file
@@ -0,0 +1,136 @@
+use core::fmt;
+use core::str;
+
+use crate::data::DeviceInfo;
+
+/// A version as the DP100 reports it, `11` means 1.1
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct FirmwareVersion(pub u16);
+
+impl FirmwareVersion {
+    pub fn major(self) -> u16 {
+        self.0 / 10
+    }
+
+    pub fn minor(self) -> u16 {
+        self.0 % 10
+    }
+}
+
+impl fmt::Display for FirmwareVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major(), self.minor())
+    }
+}
+
+/// The 12 byte serial number, shown as upper case hex
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct SerialNumber(pub [u8; 12]);
+
+impl fmt::Display for SerialNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for b in self.0.iter() {
+            write!(f, "{:02X}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Calendar date, shown as YYYY-MM-DD
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Which image the device is running.
+///
+/// The values are not documented, 0 is what units report in normal
+/// operation, 1 for the bootloader is an assumption. Anything else is
+/// kept as `Unknown`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum RunArea {
+    App,
+    Bootloader,
+    Unknown(u16),
+}
+
+impl From<u16> for RunArea {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => RunArea::App,
+            1 => RunArea::Bootloader,
+            _ => RunArea::Unknown(value),
+        }
+    }
+}
+
+impl fmt::Display for RunArea {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunArea::App => f.write_str("app"),
+            RunArea::Bootloader => f.write_str("bootloader"),
+            RunArea::Unknown(value) => write!(f, "unknown ({})", value),
+        }
+    }
+}
+
+impl DeviceInfo {
+    /// `dev_type` up to the first NUL, eg. "DP100". Bytes that are not
+    /// printable ascii end the name as well.
+    pub fn model(&self) -> &str {
+        let end = self.dev_type.iter()
+            .position(|&b| !b.is_ascii_graphic() && b != b' ')
+            .unwrap_or(self.dev_type.len());
+        // only ascii is left, this can not fail
+        str::from_utf8(&self.dev_type[..end]).unwrap_or("").trim_end()
+    }
+
+    pub fn serial(&self) -> SerialNumber {
+        SerialNumber(self.dev_sn)
+    }
+
+    pub fn hardware_version(&self) -> FirmwareVersion {
+        FirmwareVersion(self.hdw_ver)
+    }
+
+    /// Version of the application firmware
+    pub fn firmware_version(&self) -> FirmwareVersion {
+        FirmwareVersion(self.app_ver)
+    }
+
+    pub fn bootloader_version(&self) -> FirmwareVersion {
+        FirmwareVersion(self.boot_ver)
+    }
+
+    pub fn run_area(&self) -> RunArea {
+        RunArea::from(self.run_area)
+    }
+
+    /// `None` when the stored date is not a valid calendar day
+    pub fn manufactured_on(&self) -> Option<Date> {
+        let leap = self.year.is_multiple_of(4) && (!self.year.is_multiple_of(100) || self.year.is_multiple_of(400));
+        let days = match self.moon {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        if self.day == 0 || self.day > days {
+            return None;
+        }
+        Some(Date {
+            year: self.year,
+            month: self.moon,
+            day: self.day,
+        })
+    }
+}
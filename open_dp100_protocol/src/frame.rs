@@ -0,0 +1,235 @@
+use core::convert::{TryFrom, TryInto};
+
+use crc16::*;
+
+use crate::{opcode::OpCode,  data::{OpResult, OutMode, OutputState, WorkState}};
+
+pub trait Operational<const SIZE:usize> : Sized {
+    fn to_data(&self)->[u8;SIZE];
+    fn from_data(data:&[u8]) -> Result<Self,FrameError>;
+}
+
+/// One field of a payload, see `#[derive(Operational)]`.
+///
+/// `encode` and `decode` get exactly `SIZE` bytes. `big_endian` is what the
+/// field is marked with, types of a single byte ignore it.
+pub trait Field : Sized {
+    const SIZE:usize;
+    /// Byte order matters, the field needs `#[le]` or `#[be]`
+    const ORDERED:bool = false;
+
+    fn encode(&self,out:&mut [u8],big_endian:bool);
+    fn decode(data:&[u8],big_endian:bool) -> Result<Self,FrameError>;
+}
+
+macro_rules! int_field {
+    ($($ty:ty),*) => {$(
+        impl Field for $ty {
+            const SIZE:usize = core::mem::size_of::<$ty>();
+            const ORDERED:bool = core::mem::size_of::<$ty>() > 1;
+
+            fn encode(&self,out:&mut [u8],big_endian:bool){
+                let bytes = if big_endian { self.to_be_bytes() } else { self.to_le_bytes() };
+                out.copy_from_slice(&bytes);
+            }
+
+            fn decode(data:&[u8],big_endian:bool) -> Result<Self,FrameError>{
+                let bytes = data.try_into().map_err(|_| FrameError::InvalidPayload)?;
+                Ok(if big_endian { <$ty>::from_be_bytes(bytes) } else { <$ty>::from_le_bytes(bytes) })
+            }
+        }
+    )*};
+}
+
+int_field!(u8, i8, u16, i16, u32, i32);
+
+impl<const N:usize> Field for [u8;N] {
+    const SIZE:usize = N;
+
+    fn encode(&self,out:&mut [u8],_big_endian:bool){
+        out.copy_from_slice(self);
+    }
+
+    fn decode(data:&[u8],_big_endian:bool) -> Result<Self,FrameError>{
+        data.try_into().map_err(|_| FrameError::InvalidPayload)
+    }
+}
+
+impl Field for OutputState {
+    const SIZE:usize = 1;
+
+    fn encode(&self,out:&mut [u8],_big_endian:bool){
+        out[0] = u8::from(self.clone());
+    }
+
+    fn decode(data:&[u8],big_endian:bool) -> Result<Self,FrameError>{
+        Ok(OutputState::from(u8::decode(data, big_endian)?))
+    }
+}
+
+impl Field for OutMode {
+    const SIZE:usize = 1;
+
+    fn encode(&self,out:&mut [u8],_big_endian:bool){
+        out[0] = u8::from(*self);
+    }
+
+    fn decode(data:&[u8],big_endian:bool) -> Result<Self,FrameError>{
+        Ok(OutMode::from(u8::decode(data, big_endian)?))
+    }
+}
+
+impl Field for WorkState {
+    const SIZE:usize = 1;
+
+    fn encode(&self,out:&mut [u8],_big_endian:bool){
+        out[0] = u8::from(*self);
+    }
+
+    fn decode(data:&[u8],big_endian:bool) -> Result<Self,FrameError>{
+        Ok(WorkState::from(u8::decode(data, big_endian)?))
+    }
+}
+
+impl Field for OpResult {
+    const SIZE:usize = 1;
+
+    fn encode(&self,out:&mut [u8],_big_endian:bool){
+        out[0] = u8::from(self.clone());
+    }
+
+    fn decode(data:&[u8],big_endian:bool) -> Result<Self,FrameError>{
+        Ok(OpResult::from(u8::decode(data, big_endian)?))
+    }
+}
+
+/// Largest payload that fits into one 64 byte report next to header, len and crc
+pub const MAX_DATA_LEN: usize = 64 - 6;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum FrameError {
+    UnknownHeader,
+    InvalidCrc,
+    DataTooLong,
+    InvalidOpCode,
+    InvalidPayload
+}
+
+impl core::fmt::Display for FrameError{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let msg = match self{
+            FrameError::UnknownHeader => "unknown frame header",
+            FrameError::InvalidCrc => "crc mismatch",
+            FrameError::DataTooLong => "data length exceeds a report",
+            FrameError::InvalidOpCode => "unknown op code",
+            FrameError::InvalidPayload => "payload does not decode",
+        };
+        f.write_str(msg)
+    }
+}
+
+impl core::error::Error for FrameError{}
+
+#[derive(Debug,Clone)]
+pub struct Frame{
+    pub op_code: OpCode,
+    pub serial_num:u8,
+    pub op_data: [u8;64],
+    pub op_data_len:usize
+}
+
+impl Frame{
+    pub fn empty() -> Self{
+        Frame{
+            op_code: OpCode::BasicInfo,
+            serial_num:0,
+            op_data: [0;64],
+            op_data_len:0
+        }
+    }
+    /// Fails with `DataTooLong` when `data` does not fit into one report
+    pub fn new(op_code: OpCode,data:&[u8]) -> Result<Self,FrameError>{
+        let mut r = Frame::empty();
+        r.op_code = op_code;
+        r.append_data(data)?;
+        Ok(r)
+    }
+    pub fn data(&self)->&[u8]{
+        &self.op_data[0..self.op_data_len]
+    }
+
+    pub fn append_data(&mut self,data:&[u8]) -> Result<(),FrameError>{
+        if data.len() + self.op_data_len > MAX_DATA_LEN{
+            return Err(FrameError::DataTooLong);
+        }
+        self.op_data[self.op_data_len..self.op_data_len+data.len()].copy_from_slice(data);
+        self.op_data_len += data.len();
+        Ok(())
+    }
+}
+
+pub fn deserialize_in_frame(buffer:&[u8;64],frame:&mut Frame)->Result<(), FrameError>{
+    deserialize_frame(0xfa, buffer, frame)
+}
+
+/// Decode a host to device frame, this is what a device (or the simulator) receives
+pub fn deserialize_out_frame(buffer:&[u8;64],frame:&mut Frame)->Result<(), FrameError>{
+    deserialize_frame(0xfb, buffer, frame)
+}
+
+fn deserialize_frame(header:u8,buffer:&[u8;64],frame:&mut Frame)->Result<(), FrameError>{
+    if buffer[0] != header {
+        // "Unknown"
+        return Err(FrameError::UnknownHeader);
+    }
+
+    frame.op_code = match OpCode::try_from(buffer[1]) {
+        Ok(op_code) => op_code,
+        Err(_) => return Err(FrameError::InvalidOpCode)
+    };
+    frame.serial_num = buffer[2];
+    let len = buffer[3] as usize;
+    if len > MAX_DATA_LEN {
+        return Err(FrameError::DataTooLong);
+    }
+
+    //crc
+    let calc_crc = State::<MODBUS>::calculate(&buffer[0..len+4]);
+    let recv_crc = u16::from_le_bytes([buffer[4+len], buffer[4+len+1]]);
+    if calc_crc != recv_crc {
+        return Err(FrameError::InvalidCrc);
+    }
+    
+    // data copy
+    frame.op_data[0..len].copy_from_slice(&buffer[4..4+len]);
+    frame.op_data_len = len;
+    Ok(())
+}
+
+pub fn serialize_out_frame(frame:&Frame,buffer:&mut [u8;64]){
+    serialize_frame(0xfb, frame, buffer)
+}
+
+/// Encode a device to host frame, this is what a device (or the simulator) sends
+pub fn serialize_in_frame(frame:&Frame,buffer:&mut [u8;64]){
+    serialize_frame(0xfa, frame, buffer)
+}
+
+fn serialize_frame(header:u8,frame:&Frame,buffer:&mut [u8;64]){
+    buffer[0] = header;
+    buffer[1] = frame.op_code as u8;
+    buffer[2] = frame.serial_num;
+
+    let data = frame.data();
+    let data_len = data.len();
+    // know length
+    buffer[3] = data_len as u8;
+
+    // data copy
+    buffer[4..(4+data_len)].copy_from_slice(data);
+
+    //crc
+    let crc = State::<MODBUS>::calculate(&buffer[0..(4 + data_len)]);
+    buffer[4+data_len] = crc as u8;
+    buffer[4+data_len + 1] = (crc >> 8) as u8;
+}
@@ -0,0 +1,27 @@
+//! The DP100 wire protocol: frames, CRC-16/MODBUS, op codes and the
+//! payload codecs.
+//!
+//! Pure byte manipulation, `no_std` and without an allocator, so the same
+//! code runs in the host library and on a microcontroller talking to a
+//! DP100. Frames live in fixed 64 byte reports, nothing here does I/O.
+
+#![no_std]
+
+// lets `#[derive(Operational)]` name `::open_dp100_protocol` inside this crate too
+extern crate self as open_dp100_protocol;
+
+pub use opcode::OpCode;
+pub use frame::{Frame,FrameError,Field,Operational,MAX_DATA_LEN};
+pub use frame::{deserialize_in_frame,serialize_out_frame,deserialize_out_frame,serialize_in_frame};
+pub use open_dp100_derive::Operational;
+pub use data::{OutputState,OutMode,WorkState,BasicInfo,SystemInfo,DeviceInfo,BasicSet,OperationResult,OpResult,ScanOut,SerialOut};
+pub use units::{Millivolts,Milliamps,DeciCelsius,CentiWatts,Milliwatts,Milliohms};
+pub use basic_info::{DC_5V_MIN,DC_5V_MAX};
+pub use device_info::{FirmwareVersion,SerialNumber,Date,RunArea};
+
+mod frame;
+mod opcode;
+mod data;
+mod units;
+mod device_info;
+mod basic_info;
@@ -1,7 +1,9 @@
-use std::convert::TryFrom;
+use core::convert::TryFrom;
+
+use crate::frame::FrameError;
 
 #[derive(Debug)]
-#[derive(Clone)]
+#[derive(Clone,Copy,PartialEq,Eq)]
 #[repr(u8)]
 pub enum OpCode {
     None = 0x00,
@@ -14,7 +16,7 @@ pub enum OpCode {
 }
 
 impl TryFrom<u8> for OpCode {
-    type Error = String;
+    type Error = FrameError;
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0x00 => Ok(OpCode::None),
@@ -24,7 +26,7 @@ impl TryFrom<u8> for OpCode {
             0x40 => Ok(OpCode::SystemInfo),
             0x50 => Ok(OpCode::ScanOut),
             0x55 => Ok(OpCode::SerialOut),
-            _ => Err(format!("Invalid op code: {}", value)),
+            _ => Err(FrameError::InvalidOpCode),
         }
     }
 }
\ No newline at end of file
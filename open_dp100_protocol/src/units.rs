@@ -0,0 +1,102 @@
+use core::convert::TryFrom;
+use core::fmt;
+
+use crate::frame::{Field, FrameError};
+
+// value * per_unit rounded half away from zero, `None` when it is not a
+// number or does not fit into the raw type
+fn scale<T:TryFrom<i64>>(value:f64,per_unit:f64) -> Option<T>{
+    let scaled = value * per_unit;
+    if !scaled.is_finite(){
+        return None;
+    }
+    let rounded = if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 };
+    T::try_from(rounded as i64).ok()
+}
+
+macro_rules! unit {
+    ($(#[$doc:meta])* $name:ident($raw:ty), $per_unit:expr, $decimals:expr, $symbol:expr, $from:ident, $to:ident, $base:expr) => {
+        $(#[$doc])*
+        #[derive(Debug,Clone,Copy,Default,PartialEq,Eq,PartialOrd,Ord,Hash)]
+        #[repr(transparent)]
+        pub struct $name(pub $raw);
+
+        impl $name {
+            #[doc = concat!("From ", $base, ", rounded to the nearest step. `None` for NaN, infinity or out of range")]
+            pub fn $from(value:f64) -> Option<Self>{
+                scale(value, $per_unit).map($name)
+            }
+
+            #[doc = concat!("In ", $base)]
+            pub fn $to(self) -> f64{
+                self.0 as f64 / $per_unit
+            }
+        }
+
+        impl From<$raw> for $name {
+            fn from(raw:$raw) -> Self{
+                $name(raw)
+            }
+        }
+
+        impl From<$name> for $raw {
+            fn from(value:$name) -> Self{
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let raw = self.0 as i64;
+                let sign = if raw < 0 { "-" } else { "" };
+                let abs = raw.unsigned_abs();
+                let div = ($per_unit) as u64;
+                write!(f, "{}{}.{:0width$} {}", sign, abs / div, abs % div, $symbol, width = $decimals)
+            }
+        }
+
+        impl Field for $name {
+            const SIZE:usize = <$raw as Field>::SIZE;
+            const ORDERED:bool = <$raw as Field>::ORDERED;
+
+            fn encode(&self,out:&mut [u8],big_endian:bool){
+                self.0.encode(out, big_endian)
+            }
+
+            fn decode(data:&[u8],big_endian:bool) -> Result<Self,FrameError>{
+                <$raw as Field>::decode(data, big_endian).map($name)
+            }
+        }
+    };
+}
+
+unit!(
+    /// Voltage in mV, shown as volts
+    Millivolts(u16), 1000.0, 3, "V", from_volts, volts, "volts"
+);
+
+unit!(
+    /// Current in mA, shown as amps
+    Milliamps(u16), 1000.0, 3, "A", from_amps, amps, "amps"
+);
+
+unit!(
+    /// Temperature in 0.1 °C, shown as degree Celsius
+    DeciCelsius(i16), 10.0, 1, "°C", from_celsius, celsius, "degree Celsius"
+);
+
+unit!(
+    /// Power in 10 mW, shown as watts
+    CentiWatts(u16), 100.0, 2, "W", from_watts, watts, "watts"
+);
+
+unit!(
+    /// Power in mW, shown as watts
+    Milliwatts(u32), 1000.0, 3, "W", from_watts, watts, "watts"
+);
+
+
+unit!(
+    /// Resistance in mΩ, shown as ohms
+    Milliohms(u32), 1000.0, 3, "Ω", from_ohms, ohms, "ohms"
+);
@@ -0,0 +1,55 @@
+use open_dp100_protocol::{BasicInfo, DeciCelsius, Milliamps, Milliohms, Millivolts, Milliwatts, OutMode, WorkState};
+
+fn info(vout:u16,iout:u16) -> BasicInfo{
+    BasicInfo{
+        vin:Millivolts(20000),
+        vout:Millivolts(vout),
+        iout:Milliamps(iout),
+        vo_max:Millivolts(19000),
+        temp1:DeciCelsius(250),
+        temp2:DeciCelsius(250),
+        dc_5v:Millivolts(5050),
+        out_mode:OutMode::Cv,
+        work_st:WorkState::Normal,
+    }
+}
+
+#[test]
+fn power(){
+    assert_eq!(info(5000,1000).power(),Milliwatts(5000));
+    assert_eq!(info(3300,1234).power(),Milliwatts(4072));
+    assert_eq!(info(12000,0).power(),Milliwatts(0));
+    // small loads keep their milliwatts
+    assert_eq!(info(3300,1).power(),Milliwatts(3));
+    assert_eq!(info(3300,1).power().to_string(),"0.003 W");
+    assert_eq!(info(u16::MAX,u16::MAX).power(),Milliwatts(4_294_836));
+}
+
+#[test]
+fn load_resistance(){
+    assert_eq!(info(5000,1000).load_resistance(),Some(Milliohms(5000)));
+    assert_eq!(info(3300,3).load_resistance(),Some(Milliohms(1_100_000)));
+    assert_eq!(info(1000,3000).load_resistance(),Some(Milliohms(333)));
+    assert_eq!(info(5000,0).load_resistance(),None);
+    assert_eq!(Milliohms(1_100_000).to_string(),"1100.000 Ω");
+}
+
+#[test]
+fn margins(){
+    let info = info(5000,1000);
+    assert_eq!(info.dropout(),Millivolts(15000));
+    assert_eq!(info.headroom(),Millivolts(14000));
+    assert_eq!(self::info(19500,0).headroom(),Millivolts(0));
+}
+
+#[test]
+fn dc_5v_spec(){
+    let mut info = info(0,0);
+    assert!(info.dc_5v_in_spec());
+    info.dc_5v = Millivolts(4749);
+    assert!(!info.dc_5v_in_spec());
+    info.dc_5v = Millivolts(5250);
+    assert!(info.dc_5v_in_spec());
+    info.dc_5v = Millivolts(5251);
+    assert!(!info.dc_5v_in_spec());
+}
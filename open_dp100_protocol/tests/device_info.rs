@@ -0,0 +1,67 @@
+use open_dp100_protocol::{Date, DeviceInfo, FirmwareVersion, RunArea};
+
+fn info() -> DeviceInfo{
+    let mut dev_type = [0u8;16];
+    dev_type[..5].copy_from_slice(b"DP100");
+    DeviceInfo{
+        dev_type,
+        hdw_ver:11,
+        app_ver:12,
+        boot_ver:10,
+        run_area:0,
+        dev_sn:[0x53,0x49,0x4d,0,0,0,0,0,0x12,0x34,0xab,0x01],
+        year:2024,
+        moon:2,
+        day:29,
+    }
+}
+
+#[test]
+fn model_and_serial(){
+    let mut info = info();
+    assert_eq!(info.model(),"DP100");
+    assert_eq!(info.serial().to_string(),"53494D00000000001234AB01");
+
+    // garbage after the name is cut off, not shown as replacement characters
+    info.dev_type[5] = 0xff;
+    info.dev_type[6] = b'x';
+    assert_eq!(info.model(),"DP100");
+    info.dev_type = *b"DP100 \0\0\0\0\0\0\0\0\0\0";
+    assert_eq!(info.model(),"DP100");
+}
+
+#[test]
+fn versions(){
+    let info = info();
+    assert_eq!(info.hardware_version().to_string(),"1.1");
+    assert_eq!(info.firmware_version().to_string(),"1.2");
+    assert_eq!(info.bootloader_version().to_string(),"1.0");
+    assert!(info.firmware_version() > info.hardware_version());
+    assert!(FirmwareVersion(19) < FirmwareVersion(20));
+    assert_eq!((FirmwareVersion(105).major(),FirmwareVersion(105).minor()),(10,5));
+}
+
+#[test]
+fn run_area(){
+    let mut info = info();
+    assert_eq!(info.run_area(),RunArea::App);
+    info.run_area = 1;
+    assert_eq!(info.run_area(),RunArea::Bootloader);
+    info.run_area = 0x55aa;
+    assert_eq!(info.run_area(),RunArea::Unknown(0x55aa));
+}
+
+#[test]
+fn manufacture_date(){
+    let mut info = info();
+    assert_eq!(info.manufactured_on(),Some(Date{ year:2024, month:2, day:29 }));
+    assert_eq!(info.manufactured_on().unwrap().to_string(),"2024-02-29");
+
+    info.year = 2023;
+    assert_eq!(info.manufactured_on(),None);
+    info.moon = 13;
+    info.day = 1;
+    assert_eq!(info.manufactured_on(),None);
+    info.moon = 0;
+    assert_eq!(info.manufactured_on(),None);
+}
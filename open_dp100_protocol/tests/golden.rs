@@ -0,0 +1,198 @@
+use open_dp100_protocol::{deserialize_in_frame, deserialize_out_frame, serialize_in_frame, serialize_out_frame};
+use open_dp100_protocol::{BasicInfo, BasicSet, DeviceInfo, Frame, FrameError, OpCode, OpResult, OperationResult, Operational, OutputState, ScanOut, SerialOut, SystemInfo};
+use open_dp100_protocol::{CentiWatts, DeciCelsius, Milliamps, Millivolts, OutMode, WorkState, MAX_DATA_LEN};
+
+// fixed byte layouts of every payload, any change here breaks real devices
+
+fn bytes(hex:&str) -> Vec<u8>{
+    let hex:String = hex.split_whitespace().collect();
+    (0..hex.len()).step_by(2).map(|i| u8::from_str_radix(&hex[i..i+2],16).unwrap()).collect()
+}
+
+fn report(hex:&str) -> [u8;64]{
+    let data = bytes(hex);
+    let mut report = [0u8;64];
+    report[..data.len()].copy_from_slice(&data);
+    report
+}
+
+const DEVICE_INFO:&str = "44503130300000000000000000000000 0b00 1000 0d00 0100 414243444546303132333435 e607 08 0f";
+const BASIC_INFO:&str = "204e 8813 e803 384a fa00 fbff ba13 01 00";
+const BASIC_SET:&str = "21 01 8813 e803 2477 ba13";
+const SYSTEM_INFO:&str = "04 0429 2003 02";
+const SCAN_OUT:&str = "01 f401 e40c 00 e803 8813 6400";
+const SERIAL_OUT:&str = "01 e803 00 03 8813 e803 02";
+
+#[test]
+fn device_info_layout(){
+    let info = DeviceInfo::from_data(&bytes(DEVICE_INFO)).unwrap();
+    assert_eq!(&info.dev_type[..5],b"DP100");
+    assert!(info.dev_type[5..].iter().all(|&b| b == 0));
+    assert_eq!((info.hdw_ver,info.app_ver,info.boot_ver,info.run_area),(11,16,13,1));
+    assert_eq!(&info.dev_sn,b"ABCDEF012345");
+    assert_eq!((info.year,info.moon,info.day),(2022,8,15));
+    assert_eq!(info.to_data().to_vec(),bytes(DEVICE_INFO));
+}
+
+#[test]
+fn basic_info_layout(){
+    let info = BasicInfo::from_data(&bytes(BASIC_INFO)).unwrap();
+    assert_eq!((info.vin,info.vout,info.iout,info.vo_max),(Millivolts(20000),Millivolts(5000),Milliamps(1000),Millivolts(19000)));
+    assert_eq!((info.temp1,info.temp2,info.dc_5v),(DeciCelsius(250),DeciCelsius(-5),Millivolts(5050)));
+    assert_eq!((info.out_mode,info.work_st),(OutMode::Cv,WorkState::Normal));
+    assert_eq!(info.to_data().to_vec(),bytes(BASIC_INFO));
+}
+
+#[test]
+fn basic_set_layout(){
+    let set = BasicSet::from_data(&bytes(BASIC_SET)).unwrap();
+    assert_eq!(set.index,0x21);
+    assert_eq!(set.state,OutputState::On);
+    assert_eq!((set.vo_set,set.io_set,set.ovp_set,set.ocp_set),(Millivolts(5000),Milliamps(1000),Millivolts(30500),Milliamps(5050)));
+    assert_eq!(set.to_data().to_vec(),bytes(BASIC_SET));
+}
+
+#[test]
+fn system_info_layout(){
+    let info = SystemInfo::from_data(&bytes(SYSTEM_INFO)).unwrap();
+    assert_eq!((info.blk_lev,info.opp,info.opt,info.vol_kev),(4,CentiWatts(10500),DeciCelsius(800),2));
+    assert_eq!(info.to_data().to_vec(),bytes(SYSTEM_INFO));
+}
+
+#[test]
+fn operation_result_layout(){
+    assert_eq!(OperationResult::from_data(&[1]).unwrap().to_data(),[1]);
+    assert_eq!(OperationResult::from_data(&[0]).unwrap().to_data(),[0]);
+}
+
+#[test]
+fn output_layouts(){
+    let scan = ScanOut{ on_off:1, on_time:500, out_val:3300, scan_mode:0, start:1000, end:5000, step:100 };
+    assert_eq!(scan.to_data().to_vec(),bytes(SCAN_OUT));
+    assert_eq!(ScanOut::from_data(&bytes(SCAN_OUT)).unwrap(),scan);
+
+    let serial = SerialOut{ on_off:1, on_time:1000, ser_start:0, ser_end:3, ser_vi:5000, ser_vo:1000, cycle_times:2 };
+    assert_eq!(serial.to_data().to_vec(),bytes(SERIAL_OUT));
+    assert_eq!(SerialOut::from_data(&bytes(SERIAL_OUT)).unwrap(),serial);
+}
+
+#[test]
+fn request_frames(){
+    let mut buffer = [0u8;64];
+    let mut frame = Frame::new(OpCode::BasicInfo,&[]).unwrap();
+    frame.serial_num = 1;
+    serialize_out_frame(&frame,&mut buffer);
+    assert_eq!(buffer,report("fb300100309f"));
+
+    let mut frame = Frame::new(OpCode::BasicSet,&bytes(BASIC_SET)).unwrap();
+    frame.serial_num = 0;
+    serialize_out_frame(&frame,&mut buffer);
+    assert_eq!(buffer,report("fb35000a 21018813e8032477ba13 9040"));
+
+    let mut decoded = Frame::empty();
+    deserialize_out_frame(&report("fb350001038f89"),&mut decoded).unwrap();
+    assert_eq!(decoded.op_code,OpCode::BasicSet);
+    assert_eq!(decoded.data(),&[3]);
+}
+
+#[test]
+fn reply_frames(){
+    let mut frame = Frame::empty();
+    deserialize_in_frame(&report("fa400006 040429200302 7ac4"),&mut frame).unwrap();
+    assert_eq!((frame.op_code,frame.serial_num),(OpCode::SystemInfo,0));
+    assert_eq!(frame.data(),&bytes(SYSTEM_INFO)[..]);
+
+    deserialize_in_frame(&report("fa350701018249"),&mut frame).unwrap();
+    assert_eq!((frame.op_code,frame.serial_num),(OpCode::BasicSet,7));
+    assert_eq!(frame.data(),&[1]);
+
+    let mut buffer = [0u8;64];
+    serialize_in_frame(&frame,&mut buffer);
+    assert_eq!(buffer,report("fa350701018249"));
+}
+
+// every length but the right one is rejected, none of them panics
+fn rejects_wrong_lengths<const SIZE:usize,T:Operational<SIZE>>(){
+    for len in 0..=64{
+        if len != SIZE{
+            assert_eq!(T::from_data(&vec![0xff;len]).err(),Some(FrameError::InvalidPayload),"{} bytes",len);
+        }
+    }
+}
+
+#[test]
+fn wrong_payload_lengths(){
+    rejects_wrong_lengths::<40,DeviceInfo>();
+    rejects_wrong_lengths::<16,BasicInfo>();
+    rejects_wrong_lengths::<10,BasicSet>();
+    rejects_wrong_lengths::<6,SystemInfo>();
+    rejects_wrong_lengths::<12,ScanOut>();
+    rejects_wrong_lengths::<10,SerialOut>();
+    rejects_wrong_lengths::<1,OperationResult>();
+}
+
+#[test]
+fn oversized_frame_length(){
+    let mut frame = Frame::empty();
+    // a length byte past the end of the report must not index out of it
+    for len in 59..=255u8{
+        let mut buffer = report("fa30");
+        buffer[3] = len;
+        assert_eq!(deserialize_in_frame(&buffer,&mut frame),Err(FrameError::DataTooLong));
+    }
+}
+
+#[test]
+fn op_codes(){
+    use std::convert::TryFrom;
+
+    for op_code in [OpCode::DeviceInfo,OpCode::BasicInfo,OpCode::BasicSet,OpCode::SystemInfo,OpCode::ScanOut,OpCode::SerialOut]{
+        assert_eq!(OpCode::try_from(op_code as u8),Ok(op_code));
+    }
+    assert_eq!(OpCode::try_from(0x31),Err(FrameError::InvalidOpCode));
+}
+
+#[test]
+fn frame_data_limit(){
+    assert_eq!(Frame::new(OpCode::BasicSet,&[0;MAX_DATA_LEN + 1]).unwrap_err(),FrameError::DataTooLong);
+
+    let mut frame = Frame::new(OpCode::BasicSet,&[0;50]).unwrap();
+    assert_eq!(frame.append_data(&[0;8]),Ok(()));
+    assert_eq!(frame.append_data(&[0]),Err(FrameError::DataTooLong));
+    assert_eq!(frame.data().len(),MAX_DATA_LEN);
+
+    // the largest frame still fills exactly one report
+    let mut buffer = [0u8;64];
+    serialize_out_frame(&frame,&mut buffer);
+    let mut decoded = Frame::empty();
+    deserialize_out_frame(&buffer,&mut decoded).unwrap();
+    assert_eq!(decoded.data(),frame.data());
+}
+
+// only checks the guessed mapping round trips and keeps unknown values,
+// the values themselves are not confirmed by a capture
+#[test]
+fn status_codes(){
+    let modes = [(0,OutMode::Off),(1,OutMode::Cv),(2,OutMode::Cc),(3,OutMode::Unknown(3))];
+    for &(raw,mode) in modes.iter(){
+        assert_eq!(OutMode::from(raw),mode);
+        assert_eq!(u8::from(mode),raw);
+    }
+    let states = [(0,WorkState::Normal),(1,WorkState::Ovp),(2,WorkState::Ocp),(3,WorkState::Opp),(9,WorkState::Unknown(9))];
+    for &(raw,state) in states.iter(){
+        assert_eq!(WorkState::from(raw),state);
+        assert_eq!(u8::from(state),raw);
+    }
+    assert_eq!(OutMode::Cc.to_string(),"CC");
+    assert_eq!(WorkState::Unknown(9).to_string(),"unknown (9)");
+}
+
+#[test]
+fn unknown_switch_and_result_bytes_are_kept(){
+    assert_eq!(OutputState::from(0x02),OutputState::Unknown(0x02));
+    assert_eq!(u8::from(OutputState::Unknown(0x02)),0x02);
+    assert_eq!(u8::from(OutputState::On),0x01);
+    assert_eq!(OpResult::from(0x7f),OpResult::Unknown(0x7f));
+    assert_eq!(OperationResult{ result:OpResult::Unknown(0x7f) }.to_data(),[0x7f]);
+    assert_eq!(OperationResult::from_data(&[0x01]).unwrap().result,OpResult::Success);
+}
@@ -0,0 +1,136 @@
+use open_dp100_protocol::{BasicInfo, BasicSet, DeviceInfo, OpResult, OperationResult, Operational, OutputState, ScanOut, SerialOut, SystemInfo};
+use open_dp100_protocol::{CentiWatts, DeciCelsius, Milliamps, Millivolts, OutMode, WorkState};
+use proptest::prelude::*;
+
+// every payload type decodes what it encodes and the other way round,
+// so the host and the device side of the protocol agree
+
+fn output_state() -> impl Strategy<Value = OutputState>{
+    any::<u8>().prop_map(OutputState::from)
+}
+
+prop_compose!{
+    fn device_info()(dev_type in any::<[u8;16]>(),hdw_ver in any::<u16>(),app_ver in any::<u16>(),boot_ver in any::<u16>(),
+                     run_area in any::<u16>(),dev_sn in any::<[u8;12]>(),year in any::<u16>(),moon in any::<u8>(),day in any::<u8>()) -> DeviceInfo{
+        DeviceInfo{ dev_type, hdw_ver, app_ver, boot_ver, run_area, dev_sn, year, moon, day }
+    }
+}
+
+prop_compose!{
+    fn basic_info()(vin in any::<u16>(),vout in any::<u16>(),iout in any::<u16>(),vo_max in any::<u16>(),temp1 in any::<i16>(),
+                    temp2 in any::<i16>(),dc_5v in any::<u16>(),out_mode in any::<u8>(),work_st in any::<u8>()) -> BasicInfo{
+        BasicInfo{
+            vin:Millivolts(vin), vout:Millivolts(vout), iout:Milliamps(iout), vo_max:Millivolts(vo_max),
+            temp1:DeciCelsius(temp1), temp2:DeciCelsius(temp2), dc_5v:Millivolts(dc_5v),
+            out_mode:OutMode::from(out_mode), work_st:WorkState::from(work_st)
+        }
+    }
+}
+
+prop_compose!{
+    fn basic_set()(index in any::<u8>(),state in output_state(),vo_set in any::<u16>(),io_set in any::<u16>(),
+                   ovp_set in any::<u16>(),ocp_set in any::<u16>()) -> BasicSet{
+        BasicSet{ index, state, vo_set:Millivolts(vo_set), io_set:Milliamps(io_set), ovp_set:Millivolts(ovp_set), ocp_set:Milliamps(ocp_set) }
+    }
+}
+
+prop_compose!{
+    fn system_info()(blk_lev in any::<i8>(),opp in any::<u16>(),opt in any::<i16>(),vol_kev in any::<i8>()) -> SystemInfo{
+        SystemInfo{ blk_lev, opp:CentiWatts(opp), opt:DeciCelsius(opt), vol_kev }
+    }
+}
+
+prop_compose!{
+    fn scan_out()(on_off in any::<u8>(),on_time in any::<u16>(),out_val in any::<u16>(),scan_mode in any::<u8>(),
+                  start in any::<u16>(),end in any::<u16>(),step in any::<u16>()) -> ScanOut{
+        ScanOut{ on_off, on_time, out_val, scan_mode, start, end, step }
+    }
+}
+
+prop_compose!{
+    fn serial_out()(on_off in any::<u8>(),on_time in any::<u16>(),ser_start in any::<u8>(),ser_end in any::<u8>(),
+                    ser_vi in any::<u16>(),ser_vo in any::<u16>(),cycle_times in any::<u8>()) -> SerialOut{
+        SerialOut{ on_off, on_time, ser_start, ser_end, ser_vi, ser_vo, cycle_times }
+    }
+}
+
+proptest!{
+    #[test]
+    fn device_info_roundtrip(info in device_info()){
+        prop_assert_eq!(DeviceInfo::from_data(&info.to_data()).unwrap(),info);
+    }
+
+    #[test]
+    fn device_info_bytes_roundtrip(data in any::<[u8;40]>()){
+        prop_assert_eq!(DeviceInfo::from_data(&data).unwrap().to_data(),data);
+    }
+
+    #[test]
+    fn basic_info_roundtrip(info in basic_info()){
+        prop_assert_eq!(BasicInfo::from_data(&info.to_data()).unwrap(),info);
+    }
+
+    #[test]
+    fn basic_info_bytes_roundtrip(data in any::<[u8;16]>()){
+        prop_assert_eq!(BasicInfo::from_data(&data).unwrap().to_data(),data);
+    }
+
+    #[test]
+    fn basic_set_roundtrip(set in basic_set()){
+        prop_assert_eq!(BasicSet::from_data(&set.to_data()).unwrap(),set);
+    }
+
+    #[test]
+    fn basic_set_bytes_roundtrip(data in any::<[u8;10]>()){
+        prop_assert_eq!(BasicSet::from_data(&data).unwrap().to_data(),data);
+    }
+
+    #[test]
+    fn system_info_roundtrip(info in system_info()){
+        prop_assert_eq!(SystemInfo::from_data(&info.to_data()).unwrap(),info);
+    }
+
+    #[test]
+    fn system_info_bytes_roundtrip(data in any::<[u8;6]>()){
+        prop_assert_eq!(SystemInfo::from_data(&data).unwrap().to_data(),data);
+    }
+
+    #[test]
+    fn scan_out_roundtrip(scan in scan_out()){
+        prop_assert_eq!(ScanOut::from_data(&scan.to_data()).unwrap(),scan);
+    }
+
+    #[test]
+    fn scan_out_bytes_roundtrip(data in any::<[u8;12]>()){
+        prop_assert_eq!(ScanOut::from_data(&data).unwrap().to_data(),data);
+    }
+
+    #[test]
+    fn serial_out_roundtrip(serial in serial_out()){
+        prop_assert_eq!(SerialOut::from_data(&serial.to_data()).unwrap(),serial);
+    }
+
+    #[test]
+    fn serial_out_bytes_roundtrip(data in any::<[u8;10]>()){
+        prop_assert_eq!(SerialOut::from_data(&data).unwrap().to_data(),data);
+    }
+
+    #[test]
+    fn operation_result_roundtrip(raw in any::<u8>()){
+        let result = OperationResult{ result:OpResult::from(raw) };
+        prop_assert_eq!(result.to_data(),[raw]);
+        prop_assert_eq!(OperationResult::from_data(&result.to_data()).unwrap(),result);
+    }
+
+    #[test]
+    fn any_payload_decodes(data in proptest::collection::vec(any::<u8>(),0..64)){
+        // never panics, whatever the length or content
+        let _ = DeviceInfo::from_data(&data);
+        let _ = BasicInfo::from_data(&data);
+        let _ = BasicSet::from_data(&data);
+        let _ = SystemInfo::from_data(&data);
+        let _ = ScanOut::from_data(&data);
+        let _ = SerialOut::from_data(&data);
+        let _ = OperationResult::from_data(&data);
+    }
+}
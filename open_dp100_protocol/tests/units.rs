@@ -0,0 +1,49 @@
+use open_dp100_protocol::{CentiWatts, DeciCelsius, Milliamps, Millivolts, Milliwatts};
+use proptest::prelude::*;
+
+#[test]
+fn rounds_to_nearest(){
+    // 3.3 * 1000 is 3299.9999999999995 in f64, truncating gives 3299
+    assert_eq!(Millivolts::from_volts(3.3),Some(Millivolts(3300)));
+    assert_eq!(Millivolts::from_volts(0.0004),Some(Millivolts(0)));
+    assert_eq!(Millivolts::from_volts(0.0005),Some(Millivolts(1)));
+    assert_eq!(Milliamps::from_amps(1.2345),Some(Milliamps(1235)));
+    assert_eq!(DeciCelsius::from_celsius(-0.25),Some(DeciCelsius(-3)));
+    assert_eq!(CentiWatts::from_watts(105.004),Some(CentiWatts(10500)));
+    assert_eq!(Milliwatts::from_watts(0.0034),Some(Milliwatts(3)));
+}
+
+#[test]
+fn rejects_what_does_not_fit(){
+    assert_eq!(Millivolts::from_volts(-0.001),None);
+    assert_eq!(Millivolts::from_volts(65.536),None);
+    assert_eq!(Millivolts::from_volts(f64::NAN),None);
+    assert_eq!(Milliamps::from_amps(f64::INFINITY),None);
+    assert_eq!(DeciCelsius::from_celsius(-3276.9),None);
+    assert_eq!(Millivolts::from_volts(65.535),Some(Millivolts(u16::MAX)));
+}
+
+#[test]
+fn display_with_units(){
+    assert_eq!(Millivolts(3300).to_string(),"3.300 V");
+    assert_eq!(Millivolts(30500).to_string(),"30.500 V");
+    assert_eq!(Milliamps(5).to_string(),"0.005 A");
+    assert_eq!(DeciCelsius(250).to_string(),"25.0 °C");
+    assert_eq!(DeciCelsius(-5).to_string(),"-0.5 °C");
+    assert_eq!(CentiWatts(10500).to_string(),"105.00 W");
+    assert_eq!(Milliwatts(4072).to_string(),"4.072 W");
+}
+
+proptest!{
+    #[test]
+    fn volts_roundtrip(raw in any::<u16>()){
+        let mv = Millivolts(raw);
+        prop_assert_eq!(Millivolts::from_volts(mv.volts()),Some(mv));
+    }
+
+    #[test]
+    fn celsius_roundtrip(raw in any::<i16>()){
+        let t = DeciCelsius(raw);
+        prop_assert_eq!(DeciCelsius::from_celsius(t.celsius()),Some(t));
+    }
+}
@@ -0,0 +1,209 @@
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use open_dp100_protocol::{BasicInfo, BasicSet, DeviceInfo, OutputState, SystemInfo};
+
+use crate::{OpenDP100, OpenDP100Error, DefaultTransport, SessionConfig, Transport, Using};
+#[cfg(feature = "hidapi")]
+use crate::HidTransport;
+
+/// Async (tokio) front end of `OpenDP100`, enabled by the `async` feature.
+///
+/// This is not an async transport: each call runs the blocking session
+/// on tokio's blocking pool, with the device locked for the whole call
+/// so concurrent calls never mix their frames and run one after another.
+///
+/// How long a call takes is bounded by its `SessionConfig`, the device's
+/// own or the one given to `using`. Dropping the future cancels the call:
+/// no further request is written and waiting for a reply stops within a
+/// few ms, the call ends with `OpenDP100Error::Cancelled`. A request that
+/// was already written is not taken back.
+pub struct AsyncOpenDP100<T:Transport + Send + 'static = DefaultTransport>{
+    device:Arc<Mutex<OpenDP100<T>>>,
+}
+
+impl<T:Transport + Send + 'static> Clone for AsyncOpenDP100<T>{
+    fn clone(&self) -> Self{
+        Self{
+            device:self.device.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "hidapi")]
+impl AsyncOpenDP100<HidTransport>{
+    pub async fn device_count() -> Result<usize,OpenDP100Error>{
+        match tokio::task::spawn_blocking(OpenDP100::device_count).await{
+            Ok(res) => res,
+            Err(e) => Err(OpenDP100Error::driver("blocking task failed",e))
+        }
+    }
+
+    pub async fn open(device_idx:usize) -> Result<Self,OpenDP100Error>{
+        match tokio::task::spawn_blocking(move || OpenDP100::new(device_idx)).await{
+            Ok(device) => Ok(Self::new(device?)),
+            Err(e) => Err(OpenDP100Error::driver("blocking task failed",e))
+        }
+    }
+}
+
+impl<T:Transport + Send + 'static> AsyncOpenDP100<T>{
+    pub fn new(device:OpenDP100<T>) -> Self{
+        Self{
+            device:Arc::new(Mutex::new(device)),
+        }
+    }
+
+    /// Timeout and retry policy of the device, this bounds every call not made through `using`
+    pub async fn config(&self) -> Result<SessionConfig,OpenDP100Error>{
+        self.locked(|device| device.config().clone()).await
+    }
+
+    /// Takes effect once calls already queued for the device are done,
+    /// `using` changes a single call without waiting
+    pub async fn set_config(&self,config:SessionConfig) -> Result<(),OpenDP100Error>{
+        self.locked(move |device| device.set_config(config)).await
+    }
+
+    async fn locked<R,F>(&self,f:F) -> Result<R,OpenDP100Error>
+    where
+        R:Send + 'static,
+        F:FnOnce(&mut OpenDP100<T>) -> R + Send + 'static
+    {
+        let device = self.device.clone();
+        let task = tokio::task::spawn_blocking(move || {
+            let mut device = device.lock().map_err(|_| OpenDP100Error::link("device lock poisoned"))?;
+            Ok(f(&mut device))
+        });
+        match task.await{
+            Ok(res) => res,
+            Err(e) => Err(OpenDP100Error::driver("blocking task failed",e)),
+        }
+    }
+
+    /// Run single calls with `config` instead of the device's own, like
+    /// `OpenDP100::using`. Nothing is locked until a call is made
+    pub fn using(&self,config:SessionConfig) -> AsyncUsing<'_,T>{
+        AsyncUsing{
+            device:self,
+            config:Some(config),
+        }
+    }
+
+    fn own_config(&self) -> AsyncUsing<'_,T>{
+        AsyncUsing{
+            device:self,
+            config:None,
+        }
+    }
+
+    pub async fn device_info(&self)->Result<DeviceInfo,OpenDP100Error>{
+        self.own_config().device_info().await
+    }
+
+    pub async fn basic_info(&self)->Result<BasicInfo,OpenDP100Error>{
+        self.own_config().basic_info().await
+    }
+
+    pub async fn sys_info(&self)->Result<SystemInfo,OpenDP100Error>{
+        self.own_config().sys_info().await
+    }
+
+    pub async fn basic_set(&self,idx:usize)->Result<BasicSet,OpenDP100Error>{
+        self.own_config().basic_set(idx).await
+    }
+
+    pub async fn current_basic_set(&self)->Result<BasicSet,OpenDP100Error>{
+        self.own_config().current_basic_set().await
+    }
+
+    pub async fn update_basic_set(&self,set_req:&BasicSet,switch:bool)->Result<(),OpenDP100Error>{
+        self.own_config().update_basic_set(set_req, switch).await
+    }
+
+    pub async fn set_output_on(&self,on:OutputState) -> Result<(),OpenDP100Error>{
+        self.own_config().set_output_on(on).await
+    }
+
+    pub async fn switch_config(&self,idx:usize) -> Result<(),OpenDP100Error>{
+        self.own_config().switch_config(idx).await
+    }
+}
+
+/// `AsyncOpenDP100` with a `SessionConfig` for single calls, see `AsyncOpenDP100::using`
+pub struct AsyncUsing<'a,T:Transport + Send + 'static>{
+    device:&'a AsyncOpenDP100<T>,
+    // `None` takes the device's own
+    config:Option<SessionConfig>,
+}
+
+// sets the flag when the call's future is dropped before it is done
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop{
+    fn drop(&mut self){
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+impl<'a,T:Transport + Send + 'static> AsyncUsing<'a,T>{
+    async fn call<R,F>(&self,f:F) -> Result<R,OpenDP100Error>
+    where
+        R:Send + 'static,
+        F:FnOnce(Using<'_,T>) -> Result<R,OpenDP100Error> + Send + 'static
+    {
+        let device = self.device.device.clone();
+        let config = self.config.clone();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let _guard = CancelOnDrop(cancel.clone());
+        let task = tokio::task::spawn_blocking(move || {
+            let device = device.lock().map_err(|_| OpenDP100Error::link("device lock poisoned"))?;
+            let config = config.unwrap_or_else(|| device.config().clone());
+            f(device.using(&config).cancel_on(&cancel))
+        });
+
+        match task.await{
+            Ok(res) => res,
+            Err(e) => Err(OpenDP100Error::driver("blocking task failed",e)),
+        }
+    }
+
+    pub async fn device_info(&self)->Result<DeviceInfo,OpenDP100Error>{
+        self.call(|device| device.device_info()).await
+    }
+
+    pub async fn basic_info(&self)->Result<BasicInfo,OpenDP100Error>{
+        self.call(|device| device.basic_info()).await
+    }
+
+    pub async fn sys_info(&self)->Result<SystemInfo,OpenDP100Error>{
+        self.call(|device| device.sys_info()).await
+    }
+
+    pub async fn basic_set(&self,idx:usize)->Result<BasicSet,OpenDP100Error>{
+        self.call(move |device| device.basic_set(idx)).await
+    }
+
+    pub async fn current_basic_set(&self)->Result<BasicSet,OpenDP100Error>{
+        self.call(|device| device.current_basic_set()).await
+    }
+
+    pub async fn update_basic_set(&self,set_req:&BasicSet,switch:bool)->Result<(),OpenDP100Error>{
+        let set = set_req.clone();
+        self.call(move |device| device.update_basic_set(&set, switch)).await
+    }
+
+    pub async fn set_output_on(&self,on:OutputState) -> Result<(),OpenDP100Error>{
+        self.call(move |device| device.set_output_on(on)).await
+    }
+
+    pub async fn switch_config(&self,idx:usize) -> Result<(),OpenDP100Error>{
+        self.call(move |device| device.switch_config(idx)).await
+    }
+}
+
+impl<T:Transport + Send + 'static> From<OpenDP100<T>> for AsyncOpenDP100<T>{
+    fn from(device:OpenDP100<T>) -> Self{
+        Self::new(device)
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+
+use crate::{OpenDP100, OpenDP100Error, Transport};
+#[cfg(feature = "hidapi")]
+use crate::transport::HidTransport;
+#[cfg(all(feature = "hidraw", target_os = "linux"))]
+use crate::transport::HidrawTransport;
+#[cfg(feature = "libusb")]
+use crate::transport::UsbTransport;
+
+/// Driver stack used to reach the device, see `OpenDP100::open`
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum Backend{
+    /// hidapi, works everywhere hidapi does, needs the `hidapi` feature (on by default)
+    #[cfg(feature = "hidapi")]
+    Hidapi,
+    /// Linux hidraw without hidapi, needs the `hidraw` feature
+    #[cfg(all(feature = "hidraw", target_os = "linux"))]
+    Hidraw,
+    /// libusb interrupt transfers, needs the `libusb` feature
+    #[cfg(feature = "libusb")]
+    Libusb,
+}
+
+/// A DP100 found by `Backend::enumerate`, it is not opened
+#[derive(Debug,Clone,PartialEq,Eq,Hash)]
+pub struct DeviceDescriptor{
+    pub backend:Backend,
+    /// Backend specific path, stable as long as the device stays plugged in the same port.
+    /// hidapi: HID path, hidraw: `/dev/hidrawN`, libusb: `<bus>:<address>`
+    pub path:String,
+    /// USB serial number
+    pub serial:Option<String>,
+    pub manufacturer:Option<String>,
+    pub product:Option<String>,
+    pub interface_number:Option<i32>,
+}
+
+#[cfg(any(feature = "hidapi", all(feature = "hidraw", target_os = "linux"), feature = "libusb"))]
+impl Default for Backend{
+    /// The first one compiled in, in the order hidapi, hidraw, libusb
+    #[allow(unreachable_code)]
+    fn default() -> Self{
+        #[cfg(feature = "hidapi")]
+        return Backend::Hidapi;
+        #[cfg(all(feature = "hidraw", target_os = "linux"))]
+        return Backend::Hidraw;
+        #[cfg(feature = "libusb")]
+        return Backend::Libusb;
+    }
+}
+
+impl std::str::FromStr for Backend{
+    type Err = OpenDP100Error;
+
+    fn from_str(s:&str) -> Result<Self,Self::Err>{
+        match s{
+            #[cfg(feature = "hidapi")]
+            "hidapi" => Ok(Backend::Hidapi),
+            #[cfg(all(feature = "hidraw", target_os = "linux"))]
+            "hidraw" => Ok(Backend::Hidraw),
+            #[cfg(feature = "libusb")]
+            "libusb" => Ok(Backend::Libusb),
+            _ => Err(OpenDP100Error::InvalidParam("unknown or disabled backend"))
+        }
+    }
+}
+
+impl fmt::Display for Backend{
+    fn fmt(&self,f:&mut fmt::Formatter<'_>) -> fmt::Result{
+        let name = match *self{
+            #[cfg(feature = "hidapi")]
+            Backend::Hidapi => "hidapi",
+            #[cfg(all(feature = "hidraw", target_os = "linux"))]
+            Backend::Hidraw => "hidraw",
+            #[cfg(feature = "libusb")]
+            Backend::Libusb => "libusb",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The device with this serial. `Driver` rather than `NotFound` when some
+/// serial could not be read, that device may be the one asked for
+pub(crate) fn find_serial<'a>(devices:&'a [DeviceDescriptor],serial:&str) -> Result<&'a DeviceDescriptor,OpenDP100Error>{
+    match devices.iter().find(|device| device.serial.as_deref() == Some(serial)){
+        Some(device) => Ok(device),
+        None if devices.iter().any(|device| device.serial.is_none()) => Err(OpenDP100Error::Driver{
+            context:"usb serial number not readable, check the device permissions",
+            source:None,
+        }),
+        None => Err(OpenDP100Error::NotFound)
+    }
+}
+
+impl Backend{
+    /// Number of DP100s this backend can see
+    pub fn device_count(&self) -> Result<usize,OpenDP100Error>{
+        Ok(self.enumerate()?.len())
+    }
+
+    /// All DP100s this backend can see. hidapi and hidraw do not open
+    /// them. libusb has no cached strings, it opens each device for a
+    /// moment to read serial, manufacturer and product; no interface is
+    /// claimed, but a device another process holds exclusively may show
+    /// `None` there
+    pub fn enumerate(&self) -> Result<Vec<DeviceDescriptor>,OpenDP100Error>{
+        match *self{
+            #[cfg(feature = "hidapi")]
+            Backend::Hidapi => HidTransport::enumerate(),
+            #[cfg(all(feature = "hidraw", target_os = "linux"))]
+            Backend::Hidraw => match HidrawTransport::enumerate(){
+                Ok(devices) => Ok(devices.into_iter().map(DeviceDescriptor::from).collect()),
+                Err(e) => Err(OpenDP100Error::driver("reading sysfs failed",e))
+            },
+            #[cfg(feature = "libusb")]
+            Backend::Libusb => UsbTransport::enumerate(),
+        }
+    }
+
+    /// Open the `device_idx`th DP100, the order may change when devices are replugged
+    pub fn open(&self,device_idx:usize) -> Result<OpenDP100<Box<dyn Transport + Send>>,OpenDP100Error>{
+        let devices = self.enumerate()?;
+        let device = devices.get(device_idx).ok_or(OpenDP100Error::NotFound)?;
+        self.open_by_path(&device.path)
+    }
+
+    /// Open the DP100 with this USB serial number. Fails with `Driver`
+    /// rather than `NotFound` when some DP100 is there but its serial could
+    /// not be read, it may be the one asked for.
+    pub fn open_by_serial(&self,serial:&str) -> Result<OpenDP100<Box<dyn Transport + Send>>,OpenDP100Error>{
+        let devices = self.enumerate()?;
+        self.open_by_path(&find_serial(&devices,serial)?.path)
+    }
+
+    /// Open the DP100 at `path`, see `DeviceDescriptor::path`
+    pub fn open_by_path(&self,path:&str) -> Result<OpenDP100<Box<dyn Transport + Send>>,OpenDP100Error>{
+        let transport:Box<dyn Transport + Send> = match *self{
+            #[cfg(feature = "hidapi")]
+            Backend::Hidapi => Box::new(HidTransport::open_path(path)?),
+            #[cfg(all(feature = "hidraw", target_os = "linux"))]
+            Backend::Hidraw => Box::new(HidrawTransport::open_path(path)?),
+            #[cfg(feature = "libusb")]
+            Backend::Libusb => Box::new(UsbTransport::open_path(path)?),
+        };
+        Ok(OpenDP100::with_transport(transport))
+    }
+}
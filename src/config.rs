@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+/// Kinds of failure a request can run into, see `RetryPolicy::retry_on`
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub enum ErrorClass{
+    /// No reply within `SessionConfig::timeout`
+    Timeout,
+    /// Reply with a bad CRC, bad header or a payload that does not decode
+    Corrupt,
+    /// Reply to a different op code than the one asked for
+    UnexpectedOpCode,
+    /// The transport failed to write or read
+    Transport,
+}
+
+/// Wait between two attempts of the same request
+#[derive(Debug,Clone,PartialEq)]
+pub enum Backoff{
+    None,
+    Fixed(Duration),
+    /// `initial`, then doubled after every attempt, never more than `max`
+    Exponential{
+        initial:Duration,
+        max:Duration,
+    },
+}
+
+impl Backoff{
+    /// Wait before attempt number `attempt`, the first retry is attempt 1
+    pub fn delay(&self,attempt:u32) -> Duration{
+        match self{
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => *delay,
+            Backoff::Exponential{initial,max} => {
+                let factor = 1u32.checked_shl(attempt.saturating_sub(1)).unwrap_or(u32::MAX);
+                initial.saturating_mul(factor).min(*max)
+            }
+        }
+    }
+}
+
+/// When and how often a failed request is sent again
+#[derive(Debug,Clone,PartialEq)]
+pub struct RetryPolicy{
+    /// Attempts in total, the first one included, 0 is treated as 1
+    pub max_attempts:u32,
+    pub backoff:Backoff,
+    /// Failures worth another attempt, anything else is returned at once
+    pub retry_on:Vec<ErrorClass>,
+}
+
+impl RetryPolicy{
+    /// Give up after the first failure
+    pub fn never() -> Self{
+        Self{
+            max_attempts:1,
+            backoff:Backoff::None,
+            retry_on:Vec::new(),
+        }
+    }
+
+    pub fn retries(&self,class:ErrorClass) -> bool{
+        self.retry_on.contains(&class)
+    }
+}
+
+impl Default for RetryPolicy{
+    /// Up to 3 retries, only when the reply has the wrong op code
+    fn default() -> Self{
+        Self{
+            max_attempts:4,
+            backoff:Backoff::None,
+            retry_on:vec![ErrorClass::UnexpectedOpCode],
+        }
+    }
+}
+
+/// How `OpenDP100` runs a request, set per instance with
+/// `OpenDP100::set_config` or per call with `OpenDP100::using`
+#[derive(Debug,Clone,PartialEq)]
+pub struct SessionConfig{
+    /// Longest a single request may take, from dropping stale input to
+    /// the last piece of the reply. A device that keeps sending garbage
+    /// or stale replies can not stretch it
+    pub timeout:Duration,
+    pub retry:RetryPolicy,
+    /// Also take a reply carrying serial 0 as the answer, for a unit that
+    /// does not echo the request serial. On by default until the echo has
+    /// been seen on hardware, see DP100_Protocol.md. Turn it off to drop
+    /// stale replies with serial 0 too
+    pub accept_unnumbered:bool,
+}
+
+impl Default for SessionConfig{
+    fn default() -> Self{
+        Self{
+            timeout:Duration::from_millis(200),
+            retry:RetryPolicy::default(),
+            accept_unnumbered:true,
+        }
+    }
+}
@@ -1,14 +1,163 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use open_dp100_protocol::{FrameError, OpCode};
+
+use crate::config::ErrorClass;
+
+type Cause = Box<dyn Error + Send + Sync>;
+
 #[derive(Debug)]
 pub enum OpenDP100Error{
-    DRIVER,
-    DEVICE,
-    DEVICE_OPERATION,
-    INVALID_PARAM
-}
-// impl Display for OpenDP100Error{
-//     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        
-//     }
-// }
-
-// impl Error for OpenDP100Error{}
\ No newline at end of file
+    /// The usb driver stack could not be used, eg. hidapi failed to init
+    Driver{
+        context:&'static str,
+        source:Option<Cause>,
+    },
+    /// Reading from or writing to an opened device failed, eg. it was unplugged
+    Transport{
+        context:&'static str,
+        source:Option<Cause>,
+    },
+    /// No DP100 at that index, serial or path
+    NotFound,
+    InvalidParam(&'static str),
+    /// No reply in time, `op_code` is the request that went unanswered
+    Timeout{
+        op_code:Option<OpCode>,
+    },
+    /// A reply failed the CRC check, `raw` is everything read for the request
+    Crc{
+        op_code:OpCode,
+        raw:Vec<u8>,
+    },
+    /// A reply could not be framed, eg. unknown header or op code
+    Frame{
+        op_code:OpCode,
+        source:FrameError,
+        raw:Vec<u8>,
+    },
+    /// The device answered a different request
+    UnexpectedOpCode{
+        expected:OpCode,
+        received:OpCode,
+        raw:Vec<u8>,
+    },
+    /// The reply payload does not have the size its op code needs
+    PayloadLength{
+        op_code:OpCode,
+        expected:usize,
+        received:usize,
+        raw:Vec<u8>,
+    },
+    /// The device understood the request and reported it failed
+    DeviceFailure{
+        op_code:OpCode,
+    },
+    /// The caller gave up on the request, see `Using::cancel_on`
+    Cancelled{
+        op_code:OpCode,
+    },
+}
+
+impl OpenDP100Error{
+    pub(crate) fn driver<E:Into<Cause>>(context:&'static str,source:E) -> Self{
+        OpenDP100Error::Driver{
+            context,
+            source:Some(source.into()),
+        }
+    }
+
+    pub(crate) fn transport<E:Into<Cause>>(context:&'static str,source:E) -> Self{
+        OpenDP100Error::Transport{
+            context,
+            source:Some(source.into()),
+        }
+    }
+
+    /// Transport failure without an underlying error to show
+    pub(crate) fn link(context:&'static str) -> Self{
+        OpenDP100Error::Transport{
+            context,
+            source:None,
+        }
+    }
+
+    /// What kind of failure this is, for `RetryPolicy::retry_on`.
+    /// `None` for errors another attempt can not fix.
+    pub fn class(&self) -> Option<ErrorClass>{
+        match self{
+            OpenDP100Error::Transport{..} => Some(ErrorClass::Transport),
+            OpenDP100Error::Timeout{..} => Some(ErrorClass::Timeout),
+            OpenDP100Error::Crc{..}
+            | OpenDP100Error::Frame{..}
+            | OpenDP100Error::PayloadLength{..} => Some(ErrorClass::Corrupt),
+            OpenDP100Error::UnexpectedOpCode{..} => Some(ErrorClass::UnexpectedOpCode),
+            OpenDP100Error::Driver{..}
+            | OpenDP100Error::NotFound
+            | OpenDP100Error::InvalidParam(_)
+            | OpenDP100Error::DeviceFailure{..}
+            | OpenDP100Error::Cancelled{..} => None,
+        }
+    }
+
+    /// Raw bytes received for the failed request, if any
+    pub fn raw(&self) -> Option<&[u8]>{
+        match self{
+            OpenDP100Error::Crc{raw,..}
+            | OpenDP100Error::Frame{raw,..}
+            | OpenDP100Error::UnexpectedOpCode{raw,..}
+            | OpenDP100Error::PayloadLength{raw,..} => Some(raw),
+            _ => None
+        }
+    }
+}
+
+pub(crate) struct Hex<'a>(pub(crate) &'a [u8]);
+
+impl Display for Hex<'_>{
+    fn fmt(&self,f:&mut fmt::Formatter<'_>) -> fmt::Result{
+        for d in self.0{
+            write!(f,"{:02x}",d)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for OpenDP100Error{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self{
+            OpenDP100Error::Driver{context,..} => write!(f,"usb driver error: {}",context),
+            OpenDP100Error::Transport{context,..} => write!(f,"transport error: {}",context),
+            OpenDP100Error::NotFound => write!(f,"no such DP100"),
+            OpenDP100Error::InvalidParam(what) => write!(f,"invalid parameter: {}",what),
+            OpenDP100Error::Timeout{op_code:Some(op_code)} => write!(f,"no reply to {:?} in time",op_code),
+            OpenDP100Error::Timeout{op_code:None} => write!(f,"timed out"),
+            OpenDP100Error::Crc{op_code,raw} => {
+                write!(f,"reply to {:?} failed the crc check, received {}",op_code,Hex(raw))
+            }
+            OpenDP100Error::Frame{op_code,raw,..} => {
+                write!(f,"reply to {:?} is not a valid frame, received {}",op_code,Hex(raw))
+            }
+            OpenDP100Error::UnexpectedOpCode{expected,received,raw} => {
+                write!(f,"expected a {:?} reply, received {:?}: {}",expected,received,Hex(raw))
+            }
+            OpenDP100Error::PayloadLength{op_code,expected,received,raw} => {
+                write!(f,"{:?} reply carries {} bytes instead of {}: {}",op_code,received,expected,Hex(raw))
+            }
+            OpenDP100Error::DeviceFailure{op_code} => write!(f,"device reported {:?} failed",op_code),
+            OpenDP100Error::Cancelled{op_code} => write!(f,"{:?} request cancelled",op_code),
+        }
+    }
+}
+
+impl Error for OpenDP100Error{
+    fn source(&self) -> Option<&(dyn Error + 'static)>{
+        match self{
+            OpenDP100Error::Driver{source,..}
+            | OpenDP100Error::Transport{source,..} => source.as_ref().map(|e| &**e as &(dyn Error + 'static)),
+            OpenDP100Error::Frame{source,..} => Some(source),
+            _ => None
+        }
+    }
+}
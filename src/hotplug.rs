@@ -0,0 +1,150 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::backend::{Backend, DeviceDescriptor};
+use crate::error::OpenDP100Error;
+
+/// Source of the device list `HotplugWatcher` diffs, `Backend` in real use
+pub trait Enumerator{
+    fn enumerate(&self) -> Result<Vec<DeviceDescriptor>,OpenDP100Error>;
+}
+
+impl Enumerator for Backend{
+    fn enumerate(&self) -> Result<Vec<DeviceDescriptor>,OpenDP100Error>{
+        Backend::enumerate(self)
+    }
+}
+
+impl<F> Enumerator for F
+where
+    F:Fn() -> Result<Vec<DeviceDescriptor>,OpenDP100Error>
+{
+    fn enumerate(&self) -> Result<Vec<DeviceDescriptor>,OpenDP100Error>{
+        self()
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum HotplugEvent{
+    Attached(DeviceDescriptor),
+    Detached(DeviceDescriptor),
+}
+
+impl HotplugEvent{
+    pub fn device(&self) -> &DeviceDescriptor{
+        match self{
+            HotplugEvent::Attached(device) => device,
+            HotplugEvent::Detached(device) => device,
+        }
+    }
+}
+
+// a device is the same device as long as serial and path stay the same
+fn same_device(a:&DeviceDescriptor,b:&DeviceDescriptor) -> bool{
+    a.serial == b.serial && a.path == b.path
+}
+
+/// Polls an `Enumerator` and reports DP100s coming and going.
+///
+/// Devices are keyed by serial and path, a unit moved to another port is
+/// reported as detached from the old path and attached at the new one.
+/// The first `poll` reports every device already plugged in as attached.
+pub struct HotplugWatcher<E:Enumerator = Backend>{
+    enumerator:E,
+    devices:Vec<DeviceDescriptor>,
+}
+
+impl<E:Enumerator> HotplugWatcher<E>{
+    pub fn new(enumerator:E) -> Self{
+        Self{
+            enumerator,
+            devices:Vec::new(),
+        }
+    }
+
+    /// Devices present at the last successful `poll`
+    pub fn devices(&self) -> &[DeviceDescriptor]{
+        &self.devices
+    }
+
+    /// Enumerate once and return what changed since the last call.
+    ///
+    /// Detach events come before attach events. When enumeration fails
+    /// nothing is reported and the known device list is kept, so a
+    /// transient driver error does not look like every device unplugged.
+    pub fn poll(&mut self) -> Result<Vec<HotplugEvent>,OpenDP100Error>{
+        let current = self.enumerator.enumerate()?;
+        let mut events = Vec::new();
+
+        for device in self.devices.iter(){
+            if !current.iter().any(|d| same_device(d,device)){
+                events.push(HotplugEvent::Detached(device.clone()));
+            }
+        }
+        for device in current.iter(){
+            if !self.devices.iter().any(|d| same_device(d,device)){
+                events.push(HotplugEvent::Attached(device.clone()));
+            }
+        }
+
+        self.devices = current;
+        Ok(events)
+    }
+}
+
+impl<E:Enumerator + Send + 'static> HotplugWatcher<E>{
+    /// Poll every `interval` on a background thread.
+    ///
+    /// The thread runs until the returned handle is stopped or dropped,
+    /// a quiet bus does not keep it alive. Enumeration errors are
+    /// skipped, the next round tries again.
+    pub fn spawn(mut self,interval:Duration) -> HotplugHandle{
+        let (tx,rx) = mpsc::channel();
+        let (stop_tx,stop_rx) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || loop{
+            if let Ok(events) = self.poll(){
+                for event in events{
+                    if tx.send(event).is_err(){
+                        return;
+                    }
+                }
+            }
+            // doubles as the sleep, wakes up early once the handle is gone
+            match stop_rx.recv_timeout(interval){
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => return
+            }
+        });
+        HotplugHandle{
+            events:rx,
+            stop:Some(stop_tx),
+            thread:Some(thread),
+        }
+    }
+}
+
+/// A `HotplugWatcher` polling on its own thread, see `HotplugWatcher::spawn`.
+///
+/// Dropping the handle stops the thread at its next wake up, `stop` also
+/// waits for it to end.
+pub struct HotplugHandle{
+    events:Receiver<HotplugEvent>,
+    stop:Option<Sender<()>>,
+    thread:Option<JoinHandle<()>>,
+}
+
+impl HotplugHandle{
+    /// Events in the order they were seen
+    pub fn events(&self) -> &Receiver<HotplugEvent>{
+        &self.events
+    }
+
+    /// End the polling thread and wait for it, a poll in progress is finished first
+    pub fn stop(mut self){
+        self.stop.take();
+        if let Some(thread) = self.thread.take(){
+            let _ = thread.join();
+        }
+    }
+}
@@ -1,209 +1,394 @@
-use frame::{deserialize_in_frame,serialize_out_frame,Frame,Operational,FrameError};
-use hidapi::{HidApi, HidDevice};
+// without any usb backend `Backend` has no variants, code handling it can never run
+#![cfg_attr(
+    not(any(feature = "hidapi", all(feature = "hidraw", target_os = "linux"), feature = "libusb")),
+    allow(unreachable_code, unused_variables, dead_code)
+)]
+
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::fmt::Debug;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use trace::SessionTrace;
 
 pub use error::OpenDP100Error;
-pub use opcode::OpCode;
+pub use open_dp100_protocol::{OpCode,Frame,FrameError,Field,Operational,MAX_DATA_LEN};
+pub use open_dp100_protocol::{deserialize_in_frame,serialize_out_frame,deserialize_out_frame,serialize_in_frame};
+pub use frame::FrameDecoder;
+pub use transport::{Transport,ChannelTransport,Recorder,Replay,REPORT_SIZE};
+pub use transport::{RawHid,ReportId,ReportIdTransport};
+#[cfg(feature = "hidapi")]
+pub use transport::HidTransport;
+#[cfg(all(feature = "hidraw", target_os = "linux"))]
+pub use transport::{HidrawTransport,HidrawDevice};
+#[cfg(feature = "libusb")]
+pub use transport::UsbTransport;
+pub use backend::{Backend,DeviceDescriptor};
+pub use hotplug::{HotplugWatcher,HotplugHandle,HotplugEvent,Enumerator};
+pub use resilient::{ResilientOpenDP100,Connector,SerialConnector,ReconnectPolicy};
+pub use simulator::{Simulator,SimState,Load};
+pub use config::{SessionConfig,RetryPolicy,Backoff,ErrorClass};
+#[cfg(feature = "async")]
+pub use async_api::{AsyncOpenDP100,AsyncUsing};
 
-pub use data::{OutputState,BasicInfo,SystemInfo,DeviceInfo,BasicSet,OperationResult};
+pub use open_dp100_protocol::{OutputState,OutMode,WorkState,BasicInfo,SystemInfo,DeviceInfo,BasicSet,OperationResult,OpResult,ScanOut,SerialOut};
+pub use open_dp100_protocol::{Millivolts,Milliamps,DeciCelsius,CentiWatts,Milliwatts,Milliohms,DC_5V_MIN,DC_5V_MAX};
+pub use open_dp100_protocol::{FirmwareVersion,SerialNumber,Date,RunArea};
 
 mod frame;
-mod opcode;
 mod error;
-mod data;
+mod config;
+mod trace;
+pub mod transport;
+mod backend;
+mod hotplug;
+mod resilient;
+pub mod simulator;
+pub mod usbmon;
+#[cfg(feature = "async")]
+mod async_api;
+
+/// USB vendor id of the DP100
+pub const VID: u16 = 0x2e3c;
+/// USB product id of the DP100
+pub const PID: u16 = 0xaf01;
+
+/// Longest a cancellable request waits in one read before it looks at
+/// its cancel flag again
+const CANCEL_POLL: Duration = Duration::from_millis(10);
 
-const VID: u16 = 0x2e3c;
-const PID: u16 = 0xaf01;
-const READ_TIME_OUT_MS : i32 =200;
+/// Transport `OpenDP100` uses when none is named
+#[cfg(feature = "hidapi")]
+pub type DefaultTransport = HidTransport;
+#[cfg(not(feature = "hidapi"))]
+pub type DefaultTransport = Box<dyn Transport + Send>;
 
-pub struct OpenDP100{
-    hid_device:HidDevice,
+pub struct OpenDP100<T:Transport = DefaultTransport>{
+    transport:T,
+    serial:AtomicU8,
+    config:SessionConfig,
 }
-macro_rules! session_impl {
-    ($self:expr,$op_code:pat,$req:expr,$res:ident)=>{
-        {
-            let mut retry = 0;
-            let response_res = loop{
-                let frame = $self.session($req)?;
-
-                match frame.op_code {
-                    $op_code => {
-                        break $res::from_data(frame.data());
-                    }
-                    _ => {
-                        if retry == 3{
-                            break Err(FrameError::InvalidOpCode);
-                        }else{
-                            retry = retry + 1;
-                            continue;
-                        }
-                    }
-                }
-            };
 
-            match response_res{
-                Ok(response) => {
-                    Ok(response)
-                }
-                Err(_)=>{
-                    Err(OpenDP100Error::DEVICE)
-                }
-            }
-        }
+
+#[cfg(feature = "hidapi")]
+impl OpenDP100<HidTransport>{
+    pub fn device_count() -> Result<usize,OpenDP100Error>{
+        Ok(Self::enumerate()?.len())
+    }
+
+    /// All DP100s hidapi can see, none of them is opened
+    pub fn enumerate() -> Result<Vec<DeviceDescriptor>,OpenDP100Error>{
+        HidTransport::enumerate()
+    }
+
+    /// Open the `device_idx`th DP100, the order may change when devices are replugged
+    pub fn new(device_idx:usize) -> Result<Self,OpenDP100Error>{
+        let devices = Self::enumerate()?;
+        let device = devices.get(device_idx).ok_or(OpenDP100Error::NotFound)?;
+        Self::open_by_path(&device.path)
+    }
+
+    /// Open the DP100 with this USB serial number, errors as `Backend::open_by_serial`
+    pub fn open_by_serial(serial:&str) -> Result<Self,OpenDP100Error>{
+        let devices = Self::enumerate()?;
+        Self::open_by_path(&backend::find_serial(&devices,serial)?.path)
+    }
+
+    /// Open the DP100 at this HID path, see `DeviceDescriptor::path`
+    pub fn open_by_path(path:&str) -> Result<Self,OpenDP100Error>{
+        Ok(Self::with_transport(HidTransport::open_path(path)?))
     }
 }
 
+impl OpenDP100<Box<dyn Transport + Send>>{
+    /// Open the `device_idx`th DP100 with the given backend
+    pub fn open(backend:Backend,device_idx:usize) -> Result<Self,OpenDP100Error>{
+        backend.open(device_idx)
+    }
+}
 
-impl OpenDP100{
-    pub fn device_count() -> Result<usize,OpenDP100Error>{
-        let api_res = HidApi::new();
-        if api_res.is_err(){
-            return Err(OpenDP100Error::DRIVER);
+impl<T:Transport> OpenDP100<T>{
+    /// Talk to a DP100 over any transport, eg. `ChannelTransport` in tests
+    pub fn with_transport(transport:T) -> Self{
+        Self{
+            transport,
+            serial:AtomicU8::new(0),
+            config:SessionConfig::default(),
         }
+    }
 
-        let mut count = 0;
-        let api = api_res.unwrap();
-        for device in api.device_list() {
-            if device.vendor_id() == VID && device.product_id() == PID{
-                count = count + 1;
-            }
+    /// Timeout and retry policy used by every call on this instance
+    pub fn config(&self) -> &SessionConfig{
+        &self.config
+    }
+
+    pub fn set_config(&mut self,config:SessionConfig){
+        self.config = config;
+    }
+
+    /// Run single calls with `config` instead of the instance's own, eg.
+    /// `device.using(&patient).basic_info()`
+    pub fn using<'a>(&'a self,config:&'a SessionConfig) -> Using<'a,T>{
+        Using{
+            device:self,
+            config,
+            cancel:None,
         }
-        Ok(count)
     }
 
-    pub fn new(device_idx:usize) -> Option<Self>{
-        let api = HidApi::new().unwrap();
-        let mut count = 0;
-        let mut device_info = None;
+    pub fn transport(&self) -> &T{
+        &self.transport
+    }
 
-        for device in api.device_list() {
-            if device.vendor_id() == VID && device.product_id() == PID{
-                if count == device_idx{
-                    device_info = Some(device.clone());
-                }
-                count = count + 1;
+    pub fn into_transport(self) -> T{
+        self.transport
+    }
+
+    /// Serial number for the next request, 0 is never used
+    fn next_serial(&self) -> u8{
+        loop{
+            let serial = self.serial.fetch_add(1, Ordering::Relaxed).wrapping_add(1);
+            if serial != 0{
+                return serial;
             }
         }
+    }
 
-        if device_info.is_none(){
-            return None;
+    /// Drop whatever is still queued from earlier requests, a device
+    /// that never stops sending runs into `deadline`
+    fn flush(&self,op_code:OpCode,deadline:Instant) -> Result<(),OpenDP100Error>{
+        let mut input = [0u8;REPORT_SIZE];
+        while self.transport.read(&mut input,0)? > 0 {
+            if Instant::now() >= deadline{
+                return Err(OpenDP100Error::Timeout{op_code:Some(op_code)});
+            }
         }
+        Ok(())
+    }
 
-        let device = device_info.unwrap().open_device(&api).unwrap();
+    fn session(&self,config:&SessionConfig,cancel:Option<&AtomicBool>,request:&Frame,trace:&SessionTrace) -> Result<Frame,OpenDP100Error>{
+        // one deadline for the whole exchange, not for each read
+        let deadline = Instant::now() + config.timeout;
+        let mut request = request.clone();
+        request.serial_num = self.next_serial();
+        self.flush(request.op_code,deadline)?;
+        check_cancel(cancel,request.op_code)?;
 
-        Some(Self{
-            hid_device : device
-        })
-    }
-    fn write(&self,buff:&[u8]) -> Result<(),OpenDP100Error>{
-        match self.hid_device.write(&buff){
-            Ok(_)=>{
-                Ok(())
+        // write request
+        let mut output = [0u8;REPORT_SIZE];
+        serialize_out_frame(&request, &mut output );
+        trace.request(request.serial_num,&output[0..request.data().len() + 6]);
+        self.transport.write(&output)?;
+
+
+        // read response, it may come in pieces or after some garbage
+        let mut decoder = FrameDecoder::new();
+        let mut input = [0u8;REPORT_SIZE];
+        // everything read for this request, kept for error reports
+        let mut raw = Vec::new();
+        let mut broken = None;
+        loop{
+            while let Some(res) = decoder.next_frame(){
+                match res{
+                    // a device that does not echo the serial answers with 0,
+                    // then only the op code can tell if the reply is ours
+                    Ok(frame) if frame.serial_num == request.serial_num
+                        || (config.accept_unnumbered && frame.serial_num == 0) => {
+                        trace.response(&frame_bytes(&frame));
+                        return Ok(frame)
+                    }
+                    // stale reply to an earlier request
+                    Ok(_) => {}
+                    Err(e) => broken = Some(e)
+                }
             }
-            Err(_)=>{
-                Err(OpenDP100Error::DEVICE)
+            if let Some(e) = broken{
+                if decoder.pending() == 0{
+                    return Err(match e{
+                        FrameError::InvalidCrc => OpenDP100Error::Crc{
+                            op_code:request.op_code,
+                            raw
+                        },
+                        source => OpenDP100Error::Frame{
+                            op_code:request.op_code,
+                            source,
+                            raw
+                        }
+                    });
+                }
             }
-        }
-    }
 
-    fn read(&self,buff:&mut [u8]) -> Result<usize,OpenDP100Error>{
-        match self.hid_device.read_timeout(buff,READ_TIME_OUT_MS){
-            Ok(size)=>{
-                Ok(size)
+            check_cancel(cancel,request.op_code)?;
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero(){
+                return Err(OpenDP100Error::Timeout{op_code:Some(request.op_code)});
             }
-            Err(_)=>{
-                Err(OpenDP100Error::DEVICE)
+            // a cancellable request reads in short pieces to notice the flag
+            let wait = match cancel{
+                Some(_) => remaining.min(CANCEL_POLL),
+                None => remaining
+            };
+            let timeout_ms = wait.as_millis().clamp(1,i32::MAX as u128) as i32;
+            let size = self.transport.read(&mut input,timeout_ms)?;
+            if size == 0{
+                if wait < remaining{
+                    continue;
+                }
+                return Err(OpenDP100Error::Timeout{op_code:Some(request.op_code)});
             }
+
+            raw.extend_from_slice(&input[0..size]);
+            decoder.push(&input[0..size]);
         }
     }
+
+    /// One request/reply exchange, retried as `config.retry` says
+    fn request<const SIZE:usize,R:Operational<SIZE> + Debug>(&self,config:&SessionConfig,cancel:Option<&AtomicBool>,op_code:OpCode,data:&[u8]) -> Result<R,OpenDP100Error>{
+        let req = Frame::new(op_code, data).map_err(|_| OpenDP100Error::InvalidParam("request longer than a report"))?;
+        let policy = &config.retry;
+        let trace = SessionTrace::start(op_code);
+        let mut attempt = 1;
+        let res = trace.in_scope(|| loop{
+            let res = self.session(config,cancel,&req,&trace).and_then(|frame| decode_reply(op_code,&frame));
+
+            match res{
+                Ok(response) => break Ok(response),
+                Err(e) => {
+                    let retryable = e.class().is_some_and(|class| policy.retries(class));
+                    if attempt >= policy.max_attempts || !retryable{
+                        break Err(e);
+                    }
+                    thread::sleep(policy.backoff.delay(attempt));
+                    if let Err(e) = check_cancel(cancel,op_code){
+                        break Err(e);
+                    }
+                    attempt += 1;
+                }
+            }
+        });
+        trace.finish(attempt - 1,&res);
+        res
+    }
+
+    pub fn device_info(&self)->Result<DeviceInfo,OpenDP100Error>{
+        self.using(&self.config).device_info()
+    }
     
-    fn session(&self,request:&Frame) -> Result<Frame,OpenDP100Error>{
-        // write request
-        let mut output = [0u8;64];
-        serialize_out_frame(request, &mut output );
-        // print!("Write:");
-        // for d in output{
-        //     print!("{:02x}",d);
-        // }
-        // println!();
-        self.write(&output)?;
-
-
-        // read response
-        let mut frame = Frame::empty();
-        let mut input = [0u8;64];
-        self.read(&mut input)?;
-
-        // print!("Read:");
-        // for d in input{
-        //     print!("{:02x}",d);
-        // }
-        // println!();
-
-        let res = deserialize_in_frame(&input, &mut frame);
-        if res.is_err(){
-            return Err(OpenDP100Error::DEVICE);
-        }
+    pub fn basic_info(&self)->Result<BasicInfo,OpenDP100Error>{
+        self.using(&self.config).basic_info()
+    }
+
+    pub fn sys_info(&self)->Result<SystemInfo,OpenDP100Error>{
+        self.using(&self.config).sys_info()
+    }
+
+    pub fn basic_set(&self,idx:usize)->Result<BasicSet,OpenDP100Error>{
+        self.using(&self.config).basic_set(idx)
+    }
+
+    pub fn current_basic_set(&self)->Result<BasicSet,OpenDP100Error>{
+        self.using(&self.config).current_basic_set()
+    }
+
+    pub fn update_basic_set(&self,set_req:&BasicSet,switch:bool)->Result<(),OpenDP100Error>{
+        self.using(&self.config).update_basic_set(set_req, switch)
+    }
+
+}
+
+fn check_cancel(cancel:Option<&AtomicBool>,op_code:OpCode) -> Result<(),OpenDP100Error>{
+    match cancel{
+        Some(flag) if flag.load(Ordering::Acquire) => Err(OpenDP100Error::Cancelled{op_code}),
+        _ => Ok(())
+    }
+}
+
+// the reply as it came over the wire, header to crc
+fn frame_bytes(frame:&Frame) -> Vec<u8>{
+    let mut buffer = [0u8;REPORT_SIZE];
+    serialize_in_frame(frame, &mut buffer);
+    buffer[0..frame.data().len() + 6].to_vec()
+}
+
+fn decode_reply<const SIZE:usize,R:Operational<SIZE>>(op_code:OpCode,frame:&Frame) -> Result<R,OpenDP100Error>{
+    if frame.op_code != op_code{
+        return Err(OpenDP100Error::UnexpectedOpCode{
+            expected:op_code,
+            received:frame.op_code,
+            raw:frame_bytes(frame)
+        });
+    }
+    if frame.data().len() != SIZE{
+        return Err(OpenDP100Error::PayloadLength{
+            op_code,
+            expected:SIZE,
+            received:frame.data().len(),
+            raw:frame_bytes(frame)
+        });
+    }
+    R::from_data(frame.data()).map_err(|source| OpenDP100Error::Frame{
+        op_code,
+        source,
+        raw:frame_bytes(frame)
+    })
+}
 
-        Ok(frame)
+/// `OpenDP100` borrowed with a `SessionConfig` for a single call, see `OpenDP100::using`
+pub struct Using<'a,T:Transport>{
+    device:&'a OpenDP100<T>,
+    config:&'a SessionConfig,
+    cancel:Option<&'a AtomicBool>,
+}
+
+impl<'a,T:Transport> Using<'a,T>{
+    /// Give up with `Cancelled` once `cancel` is set. It is looked at
+    /// before each request is written, between reads and between retries,
+    /// a request already written is not taken back
+    pub fn cancel_on(self,cancel:&'a AtomicBool) -> Self{
+        Self{
+            cancel:Some(cancel),
+            ..self
+        }
     }
 
     pub fn device_info(&self)->Result<DeviceInfo,OpenDP100Error>{
-        let req = Frame::new(OpCode::DeviceInfo, &[0u8;0]);
-        session_impl!(self,OpCode::DeviceInfo,&req,DeviceInfo)
+        self.device.request(self.config, self.cancel, OpCode::DeviceInfo, &[0u8;0])
     }
     
     pub fn basic_info(&self)->Result<BasicInfo,OpenDP100Error>{
-        let req = Frame::new(OpCode::BasicInfo, &[0u8;0]);
-        session_impl!(self,OpCode::BasicInfo,&req,BasicInfo)
+        self.device.request(self.config, self.cancel, OpCode::BasicInfo, &[0u8;0])
     }
 
     pub fn sys_info(&self)->Result<SystemInfo,OpenDP100Error>{
-        let req = Frame::new(OpCode::SystemInfo, &[0u8;0]);
-        session_impl!(self,OpCode::SystemInfo,&req,SystemInfo)
+        self.device.request(self.config, self.cancel, OpCode::SystemInfo, &[0u8;0])
     }
 
     pub fn basic_set(&self,idx:usize)->Result<BasicSet,OpenDP100Error>{
-        if idx>10{
-            return Err(OpenDP100Error::INVALID_PARAM);
+        if idx >= simulator::PRESET_COUNT{
+            return Err(OpenDP100Error::InvalidParam("preset index out of range"));
         }
-        let req = Frame::new(OpCode::BasicSet, &[idx as u8;1]);
-        session_impl!(self,OpCode::BasicSet,&req,BasicSet)
+        self.device.request(self.config, self.cancel, OpCode::BasicSet, &[idx as u8;1])
     }
 
     pub fn current_basic_set(&self)->Result<BasicSet,OpenDP100Error>{
-        let req = Frame::new(OpCode::BasicSet, &[0x80u8;1]);
-        session_impl!(self,OpCode::BasicSet,&req,BasicSet)
+        self.device.request(self.config, self.cancel, OpCode::BasicSet, &[0x80u8;1])
     }
 
     pub fn update_basic_set(&self,set_req:&BasicSet,switch:bool)->Result<(),OpenDP100Error>{
         let mut set = (*set_req).clone();
-        set.index = {if switch {0xa0} else {0x20}} + set.index;
-
-        let req: Frame = Frame::new(OpCode::BasicSet, &set.to_data());
-        let r = session_impl!(self,OpCode::BasicSet,&req,OperationResult);
-        
-        match r {
-            Ok(ok) => {
-                match ok.result {
-                    data::OpResult::Success=>{
-                        Ok(())
-                    }
-                    _=>{
-                        Err(OpenDP100Error::DEVICE)
-                    }
-                } 
+        set.index += if switch {0xa0} else {0x20};
+
+        let r:OperationResult = self.device.request(self.config, self.cancel, OpCode::BasicSet, &set.to_data())?;
+        match r.result {
+            OpResult::Success=>{
+                Ok(())
             }
-            Err(e)=>{
-                Err(e)
+            _=>{
+                Err(OpenDP100Error::DeviceFailure{op_code:OpCode::BasicSet})
             }
         }
     }
 
-}
-
-/** High level api */
-impl OpenDP100 {
-    
     pub fn set_output_on(&self,on:OutputState) -> Result<(),OpenDP100Error>{
         let mut basic_set = self.current_basic_set()?;
         if basic_set.state == on{
@@ -219,5 +404,17 @@ impl OpenDP100 {
         self.update_basic_set(&config_set, true)?;
         Ok(())
     }
+}
+
+/** High level api */
+impl<T:Transport> OpenDP100<T> {
+    
+    pub fn set_output_on(&self,on:OutputState) -> Result<(),OpenDP100Error>{
+        self.using(&self.config).set_output_on(on)
+    }
+    
+    pub fn switch_config(&self,idx:usize) -> Result<(),OpenDP100Error>{
+        self.using(&self.config).switch_config(idx)
+    }
 
-}
\ No newline at end of file
+}
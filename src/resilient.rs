@@ -0,0 +1,225 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use open_dp100_protocol::{BasicInfo, BasicSet, DeviceInfo, OutputState, SystemInfo};
+
+use crate::backend::Backend;
+use crate::config::SessionConfig;
+use crate::error::OpenDP100Error;
+use crate::transport::Transport;
+use crate::OpenDP100;
+
+/// Opens a fresh transport to the same unit, used by `ResilientOpenDP100`
+pub trait Connector{
+    type Transport:Transport;
+
+    fn connect(&self) -> Result<Self::Transport,OpenDP100Error>;
+}
+
+impl<T,F> Connector for F
+where
+    T:Transport,
+    F:Fn() -> Result<T,OpenDP100Error>
+{
+    type Transport = T;
+
+    fn connect(&self) -> Result<T,OpenDP100Error>{
+        self()
+    }
+}
+
+/// Finds the unit by its USB serial on every connect, so it is found
+/// again even when it comes back on another path
+#[derive(Debug,Clone)]
+pub struct SerialConnector{
+    pub backend:Backend,
+    pub serial:String,
+}
+
+impl Connector for SerialConnector{
+    type Transport = Box<dyn Transport + Send>;
+
+    fn connect(&self) -> Result<Self::Transport,OpenDP100Error>{
+        Ok(self.backend.open_by_serial(&self.serial)?.into_transport())
+    }
+}
+
+/// How hard `ResilientOpenDP100` tries before giving up on a call
+#[derive(Debug,Clone,PartialEq)]
+pub struct ReconnectPolicy{
+    /// Reconnect and retry this many times after the first failure
+    pub max_attempts:u32,
+    /// Wait before each reconnect, the device needs a moment to enumerate again
+    pub delay:Duration,
+    /// Calls in a row that have to time out before the link counts as
+    /// dead, a single slow reply is returned as `Timeout` and the handle
+    /// kept. 0 never reconnects on timeouts
+    pub timeouts_before_reconnect:u32,
+}
+
+impl Default for ReconnectPolicy{
+    fn default() -> Self{
+        Self{
+            max_attempts:3,
+            delay:Duration::from_millis(500),
+            timeouts_before_reconnect:3,
+        }
+    }
+}
+
+/// `OpenDP100` that survives the USB link dropping.
+///
+/// When a call fails with `Transport`, or is the
+/// `ReconnectPolicy::timeouts_before_reconnect`th call in a row to fail
+/// with `Timeout`, the transport is assumed dead. It is dropped, a new one
+/// is opened through the `Connector` and the call is sent again, up to
+/// `ReconnectPolicy::max_attempts` times. Any other error is returned
+/// straight away.
+///
+/// The `SessionConfig` set here is handed to every reconnected device, a
+/// single call can still use its own with
+/// `call(|device| device.using(&patient).basic_info())`.
+pub struct ResilientOpenDP100<C:Connector>{
+    connector:C,
+    policy:ReconnectPolicy,
+    config:SessionConfig,
+    device:Mutex<Option<OpenDP100<C::Transport>>>,
+    reconnects:AtomicUsize,
+    // calls in a row that timed out on the current handle
+    timeouts:AtomicU32,
+}
+
+impl ResilientOpenDP100<SerialConnector>{
+    /// Open the unit with this USB serial, and find it again by serial after a drop
+    pub fn open_by_serial(backend:Backend,serial:&str) -> Result<Self,OpenDP100Error>{
+        Self::new(SerialConnector{
+            backend,
+            serial:serial.to_string(),
+        })
+    }
+}
+
+impl<C:Connector> ResilientOpenDP100<C>{
+    /// Connect once, fails if the unit is not there right now
+    pub fn new(connector:C) -> Result<Self,OpenDP100Error>{
+        let device = OpenDP100::with_transport(connector.connect()?);
+        Ok(Self{
+            connector,
+            policy:ReconnectPolicy::default(),
+            config:SessionConfig::default(),
+            device:Mutex::new(Some(device)),
+            reconnects:AtomicUsize::new(0),
+            timeouts:AtomicU32::new(0),
+        })
+    }
+
+    pub fn with_policy(mut self,policy:ReconnectPolicy) -> Self{
+        self.policy = policy;
+        self
+    }
+
+    pub fn policy(&self) -> &ReconnectPolicy{
+        &self.policy
+    }
+
+    pub fn with_config(mut self,config:SessionConfig) -> Self{
+        self.set_config(config);
+        self
+    }
+
+    /// Timeout and retry policy of the device, kept across reconnects
+    pub fn config(&self) -> &SessionConfig{
+        &self.config
+    }
+
+    pub fn set_config(&mut self,config:SessionConfig){
+        let device = self.device.get_mut().unwrap_or_else(|e| e.into_inner());
+        if let Some(device) = device.as_mut(){
+            device.set_config(config.clone());
+        }
+        self.config = config;
+    }
+
+    /// Number of successful reconnects so far
+    pub fn reconnect_count(&self) -> usize{
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    // a stale handle shows up as failing io, or as a device that stopped answering
+    fn is_dead_link<R>(&self,res:&Result<R,OpenDP100Error>) -> bool{
+        match res{
+            Err(OpenDP100Error::Transport{..}) => true,
+            Err(OpenDP100Error::Timeout{..}) => {
+                let timeouts = self.timeouts.fetch_add(1, Ordering::Relaxed) + 1;
+                self.policy.timeouts_before_reconnect > 0 && timeouts >= self.policy.timeouts_before_reconnect
+            }
+            _ => {
+                self.timeouts.store(0, Ordering::Relaxed);
+                false
+            }
+        }
+    }
+
+    /// Run `f` against the device, reconnecting as the policy allows
+    pub fn call<R,F>(&self,f:F) -> Result<R,OpenDP100Error>
+    where
+        F:Fn(&OpenDP100<C::Transport>) -> Result<R,OpenDP100Error>
+    {
+        let mut device = self.device.lock().map_err(|_| OpenDP100Error::link("device lock poisoned"))?;
+        let mut attempt = 0;
+        loop{
+            let res = match device.as_ref(){
+                Some(dev) => f(dev),
+                None => Err(OpenDP100Error::link("not connected"))
+            };
+            if !self.is_dead_link(&res) || attempt >= self.policy.max_attempts{
+                return res;
+            }
+            attempt += 1;
+            self.timeouts.store(0, Ordering::Relaxed);
+            // close the stale handle before the unit shows up again
+            *device = None;
+            thread::sleep(self.policy.delay);
+            if let Ok(transport) = self.connector.connect(){
+                let mut fresh = OpenDP100::with_transport(transport);
+                fresh.set_config(self.config.clone());
+                *device = Some(fresh);
+                self.reconnects.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    pub fn device_info(&self)->Result<DeviceInfo,OpenDP100Error>{
+        self.call(|device| device.device_info())
+    }
+
+    pub fn basic_info(&self)->Result<BasicInfo,OpenDP100Error>{
+        self.call(|device| device.basic_info())
+    }
+
+    pub fn sys_info(&self)->Result<SystemInfo,OpenDP100Error>{
+        self.call(|device| device.sys_info())
+    }
+
+    pub fn basic_set(&self,idx:usize)->Result<BasicSet,OpenDP100Error>{
+        self.call(|device| device.basic_set(idx))
+    }
+
+    pub fn current_basic_set(&self)->Result<BasicSet,OpenDP100Error>{
+        self.call(|device| device.current_basic_set())
+    }
+
+    pub fn update_basic_set(&self,set_req:&BasicSet,switch:bool)->Result<(),OpenDP100Error>{
+        self.call(|device| device.update_basic_set(set_req, switch))
+    }
+
+    pub fn set_output_on(&self,on:OutputState) -> Result<(),OpenDP100Error>{
+        self.call(|device| device.set_output_on(on.clone()))
+    }
+
+    pub fn switch_config(&self,idx:usize) -> Result<(),OpenDP100Error>{
+        self.call(|device| device.switch_config(idx))
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+use open_dp100_protocol::{BasicInfo, BasicSet, DeviceInfo, OpResult, OperationResult, OutMode, OutputState, SystemInfo, WorkState};
+use open_dp100_protocol::{CentiWatts, DeciCelsius, Milliamps, Millivolts};
+use open_dp100_protocol::{deserialize_out_frame, serialize_in_frame, Frame, OpCode, Operational};
+
+use crate::error::OpenDP100Error;
+use crate::transport::{Transport, REPORT_SIZE};
+
+/// Number of `BasicSet` presets stored in a DP100
+pub const PRESET_COUNT: usize = 10;
+
+// BASIC_SET index flags, see DP100_Protocol.md
+const FLAG_MODIFY: u8 = 0x20;
+const FLAG_ACTIVATE: u8 = 0x80;
+
+/// What is connected to the simulated output
+#[derive(Debug,Clone,PartialEq)]
+pub enum Load{
+    /// Nothing connected, no current flows
+    Open,
+    /// A resistor, value in ohm
+    Resistor(f32),
+    /// Output shorted, trips OCP as soon as the output is on
+    Short,
+}
+
+/// Complete state of a simulated DP100, reachable via `Simulator::state`
+#[derive(Debug,Clone)]
+pub struct SimState{
+    pub presets:[BasicSet;PRESET_COUNT],
+    pub active:usize,
+    pub output:OutputState,
+    pub load:Load,
+    pub vin:Millivolts,
+    pub temp:DeciCelsius,
+    pub work_st:WorkState,
+    pub system:SystemInfo,
+    pub device:DeviceInfo,
+}
+
+impl Default for SimState{
+    fn default() -> Self{
+        let mut dev_type = [0u8;16];
+        dev_type[0..5].copy_from_slice(b"DP100");
+
+        SimState{
+            presets:std::array::from_fn(|i| BasicSet{
+                index:i as u8,
+                state:OutputState::Off,
+                vo_set:Millivolts(5000),
+                io_set:Milliamps(1000),
+                ovp_set:Millivolts(30500),
+                ocp_set:Milliamps(5050),
+            }),
+            active:0,
+            output:OutputState::Off,
+            load:Load::Open,
+            vin:Millivolts(20000),
+            temp:DeciCelsius(250),
+            work_st:WorkState::Normal,
+            system:SystemInfo{
+                blk_lev:4,
+                opp:CentiWatts(10500),
+                opt:DeciCelsius(800),
+                vol_kev:2,
+            },
+            device:DeviceInfo{
+                dev_type,
+                hdw_ver:11,
+                app_ver:12,
+                boot_ver:10,
+                run_area:0,
+                dev_sn:[0x53,0x49,0x4d,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x00,0x01],
+                year:2023,
+                moon:5,
+                day:1,
+            },
+        }
+    }
+}
+
+impl SimState{
+    /// Readings the device would report right now
+    pub fn basic_info(&self) -> BasicInfo{
+        let set = &self.presets[self.active];
+        let vo_max = Millivolts((self.vin.0 as u32 * 95 / 100) as u16);
+
+        let (vout,iout,out_mode) = if self.output == OutputState::Off{
+            (Millivolts(0),Milliamps(0),OutMode::Off)
+        }else{
+            let vo_set = set.vo_set.min(vo_max);
+            match self.load{
+                Load::Open => (vo_set,Milliamps(0),OutMode::Cv),
+                Load::Short => (Millivolts(0),set.io_set,OutMode::Cc),
+                Load::Resistor(ohm) => {
+                    // mV / ohm = mA
+                    let i = vo_set.0 as f32 / ohm;
+                    if i > set.io_set.0 as f32{
+                        let v = (set.io_set.0 as f32 * ohm).round().min(vo_set.0 as f32);
+                        (Millivolts(v as u16),set.io_set,OutMode::Cc)
+                    }else{
+                        (vo_set,Milliamps(i.round() as u16),OutMode::Cv)
+                    }
+                }
+            }
+        };
+
+        BasicInfo{
+            vin:self.vin,
+            vout,
+            iout,
+            vo_max,
+            temp1:self.temp,
+            temp2:self.temp,
+            dc_5v:Millivolts(5000),
+            out_mode,
+            work_st:self.work_st,
+        }
+    }
+
+    /// Turn the output off if the current readings violate a protection limit
+    fn check_protection(&mut self){
+        if self.output == OutputState::Off{
+            return;
+        }
+        let set = &self.presets[self.active];
+        let info = self.basic_info();
+        // opp is in 10mW
+        let power = info.power().0 / 10;
+
+        // raw codes follow the unverified guess in DP100_Protocol.md
+        let work_st = if self.load == Load::Short || info.iout > set.ocp_set{
+            WorkState::Ocp
+        }else if info.vout > set.ovp_set{
+            WorkState::Ovp
+        }else if power > self.system.opp.0 as u32{
+            WorkState::Opp
+        }else{
+            WorkState::Normal
+        };
+
+        if work_st != WorkState::Normal{
+            self.work_st = work_st;
+            self.set_output(OutputState::Off);
+        }
+    }
+
+    fn set_output(&mut self,state:OutputState){
+        if state == OutputState::On{
+            // turning on again clears a previous protection trip
+            self.work_st = WorkState::Normal;
+        }
+        self.presets[self.active].state = state.clone();
+        self.output = state;
+    }
+
+    fn basic_set(&mut self,data:&[u8]) -> Option<Frame>{
+        if data.len() == 1{
+            // query
+            let idx = if data[0] & FLAG_ACTIVATE != 0{
+                self.active
+            }else{
+                (data[0] & 0x0f) as usize
+            };
+            if idx >= PRESET_COUNT{
+                return None;
+            }
+            let mut set = self.presets[idx].clone();
+            set.index = idx as u8;
+            return Frame::new(OpCode::BasicSet,&set.to_data()).ok();
+        }
+
+        if data.len() != 10{
+            return None;
+        }
+        let mut set = BasicSet::from_data(data).ok()?;
+        let flags = set.index & 0xf0;
+        let idx = (set.index & 0x0f) as usize;
+        let mut result = OpResult::Failed;
+
+        let known_state = !matches!(set.state, OutputState::Unknown(_));
+        if idx < PRESET_COUNT && known_state && flags & (FLAG_MODIFY | FLAG_ACTIVATE) != 0{
+            set.index = idx as u8;
+            if flags & FLAG_MODIFY != 0{
+                self.presets[idx] = set.clone();
+            }
+            if flags & FLAG_ACTIVATE != 0{
+                self.active = idx;
+            }
+            if idx == self.active{
+                self.set_output(self.presets[idx].state.clone());
+            }
+            self.check_protection();
+            result = OpResult::Success;
+        }
+
+        Frame::new(OpCode::BasicSet,&OperationResult{ result }.to_data()).ok()
+    }
+
+    /// Process one host request, `None` means the device stays silent
+    pub(crate) fn handle(&mut self,request:&Frame) -> Option<Frame>{
+        match request.op_code{
+            OpCode::DeviceInfo => Frame::new(OpCode::DeviceInfo,&self.device.to_data()).ok(),
+            OpCode::BasicInfo => {
+                self.check_protection();
+                Frame::new(OpCode::BasicInfo,&self.basic_info().to_data()).ok()
+            }
+            OpCode::SystemInfo => Frame::new(OpCode::SystemInfo,&self.system.to_data()).ok(),
+            OpCode::BasicSet => self.basic_set(request.data()),
+            _ => None,
+        }
+    }
+}
+
+/// A software DP100, usable anywhere a `Transport` is expected.
+///
+/// Each report written is decoded as a host frame, handled against the
+/// simulated state, and the reply is queued for the next `read`. A `read`
+/// with nothing queued waits out its timeout like a real device would.
+pub struct Simulator{
+    state:Mutex<SimState>,
+    replies:Mutex<VecDeque<[u8;REPORT_SIZE]>>,
+    queued:Condvar,
+}
+
+impl Default for Simulator{
+    fn default() -> Self{
+        Self::with_state(SimState::default())
+    }
+}
+
+impl Simulator{
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    pub fn with_state(state:SimState) -> Self{
+        Simulator{
+            state:Mutex::new(state),
+            replies:Mutex::new(VecDeque::new()),
+            queued:Condvar::new(),
+        }
+    }
+
+    /// Inspect or modify the simulated device
+    pub fn state(&self) -> Result<MutexGuard<'_,SimState>,OpenDP100Error>{
+        self.state.lock().map_err(|_| OpenDP100Error::link("simulator lock poisoned"))
+    }
+
+    pub fn set_load(&self,load:Load) -> Result<(),OpenDP100Error>{
+        let mut state = self.state()?;
+        state.load = load;
+        state.check_protection();
+        Ok(())
+    }
+}
+
+impl Transport for Simulator{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        let mut request = Frame::empty();
+        if deserialize_out_frame(report,&mut request).is_err(){
+            // a real device ignores garbage
+            return Ok(());
+        }
+
+        let reply = self.state()?.handle(&request);
+        if let Some(mut reply) = reply{
+            reply.serial_num = request.serial_num;
+            let mut buffer = [0u8;REPORT_SIZE];
+            serialize_in_frame(&reply,&mut buffer);
+            self.replies.lock().map_err(|_| OpenDP100Error::link("simulator lock poisoned"))?.push_back(buffer);
+            self.queued.notify_all();
+        }
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        let replies = self.replies.lock().map_err(|_| OpenDP100Error::link("simulator lock poisoned"))?;
+        // a negative timeout waits for good, as with hidapi
+        let mut replies = if timeout_ms < 0{
+            self.queued.wait_while(replies,|replies| replies.is_empty())
+                .map_err(|_| OpenDP100Error::link("simulator lock poisoned"))?
+        }else{
+            self.queued.wait_timeout_while(replies,Duration::from_millis(timeout_ms as u64),|replies| replies.is_empty())
+                .map_err(|_| OpenDP100Error::link("simulator lock poisoned"))?.0
+        };
+        match replies.pop_front(){
+            Some(reply) => {
+                *report = reply;
+                Ok(REPORT_SIZE)
+            }
+            None => Ok(0),
+        }
+    }
+}
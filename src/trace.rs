@@ -0,0 +1,111 @@
+// Frame level tracing for `OpenDP100::request`, enabled by the `tracing` feature.
+//
+// Every request is one `dp100_session` span at debug level with the fields
+//   op_code, serial, request, response (hex), retries, result, latency_us
+// and the decoded reply as a debug event inside it. Without the feature
+// all of this compiles to nothing.
+
+#[cfg(feature = "tracing")]
+pub(crate) use enabled::SessionTrace;
+#[cfg(not(feature = "tracing"))]
+pub(crate) use disabled::SessionTrace;
+
+#[cfg(feature = "tracing")]
+mod enabled{
+    use std::fmt::Debug;
+    use std::time::Instant;
+
+    use tracing::field::Empty;
+
+    use crate::error::{Hex, OpenDP100Error};
+    use open_dp100_protocol::OpCode;
+
+    pub(crate) struct SessionTrace{
+        span:tracing::Span,
+        start:Instant,
+    }
+
+    impl SessionTrace{
+        pub(crate) fn start(op_code:OpCode) -> Self{
+            let span = tracing::debug_span!(
+                "dp100_session",
+                op_code = ?op_code,
+                serial = Empty,
+                request = Empty,
+                response = Empty,
+                retries = Empty,
+                result = Empty,
+                latency_us = Empty,
+            );
+            Self{
+                span,
+                start:Instant::now(),
+            }
+        }
+
+        pub(crate) fn in_scope<T>(&self,f:impl FnOnce() -> T) -> T{
+            self.span.in_scope(f)
+        }
+
+        /// The request as written, recorded again on every retry
+        pub(crate) fn request(&self,serial:u8,bytes:&[u8]){
+            if self.span.is_disabled(){
+                return;
+            }
+            self.span.record("serial",serial);
+            self.span.record("request",tracing::field::display(Hex(bytes)));
+        }
+
+        pub(crate) fn response(&self,bytes:&[u8]){
+            if self.span.is_disabled(){
+                return;
+            }
+            self.span.record("response",tracing::field::display(Hex(bytes)));
+        }
+
+        pub(crate) fn finish<R:Debug>(&self,retries:u32,res:&Result<R,OpenDP100Error>){
+            self.span.record("retries",retries);
+            self.span.record("latency_us",self.start.elapsed().as_micros() as u64);
+            match res{
+                Ok(reply) => {
+                    self.span.record("result","ok");
+                    self.in_scope(|| tracing::debug!(reply = ?reply,"decoded"));
+                }
+                Err(e) => {
+                    self.span.record("result",tracing::field::display(e));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+mod disabled{
+    use std::fmt::Debug;
+
+    use crate::error::OpenDP100Error;
+    use open_dp100_protocol::OpCode;
+
+    pub(crate) struct SessionTrace;
+
+    impl SessionTrace{
+        #[inline]
+        pub(crate) fn start(_op_code:OpCode) -> Self{
+            SessionTrace
+        }
+
+        #[inline]
+        pub(crate) fn in_scope<T>(&self,f:impl FnOnce() -> T) -> T{
+            f()
+        }
+
+        #[inline]
+        pub(crate) fn request(&self,_serial:u8,_bytes:&[u8]){}
+
+        #[inline]
+        pub(crate) fn response(&self,_bytes:&[u8]){}
+
+        #[inline]
+        pub(crate) fn finish<R:Debug>(&self,_retries:u32,_res:&Result<R,OpenDP100Error>){}
+    }
+}
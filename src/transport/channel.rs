@@ -0,0 +1,62 @@
+use std::sync::Mutex;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+use crate::error::OpenDP100Error;
+use super::{Transport,REPORT_SIZE};
+
+/// In-memory transport, one end of a connected pair.
+///
+/// Whatever is written to one end can be read from the other,
+/// so a test can play the device side without any hardware.
+pub struct ChannelTransport{
+    tx:Sender<[u8;REPORT_SIZE]>,
+    rx:Mutex<Receiver<[u8;REPORT_SIZE]>>,
+}
+
+impl ChannelTransport{
+    /// Create two connected ends, usually `(host,device)`
+    pub fn pair() -> (Self,Self){
+        let (a_tx,a_rx) = channel();
+        let (b_tx,b_rx) = channel();
+        (
+            Self{ tx:a_tx, rx:Mutex::new(b_rx) },
+            Self{ tx:b_tx, rx:Mutex::new(a_rx) },
+        )
+    }
+}
+
+impl Transport for ChannelTransport{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        match self.tx.send(*report){
+            Ok(_)=>{
+                Ok(())
+            }
+            Err(_)=>{
+                // the other end is gone
+                Err(OpenDP100Error::link("channel closed"))
+            }
+        }
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        let rx = self.rx.lock().map_err(|_| OpenDP100Error::link("channel lock poisoned"))?;
+        let res = if timeout_ms < 0 {
+            rx.recv().map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            rx.recv_timeout(Duration::from_millis(timeout_ms as u64))
+        };
+        match res{
+            Ok(data)=>{
+                *report = data;
+                Ok(REPORT_SIZE)
+            }
+            Err(RecvTimeoutError::Timeout)=>{
+                Ok(0)
+            }
+            Err(RecvTimeoutError::Disconnected)=>{
+                Err(OpenDP100Error::link("channel closed"))
+            }
+        }
+    }
+}
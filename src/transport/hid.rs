@@ -0,0 +1,103 @@
+use std::ffi::CString;
+
+use hidapi::{HidApi, HidDevice};
+
+use crate::error::OpenDP100Error;
+use crate::backend::{Backend,DeviceDescriptor};
+use crate::{VID,PID};
+use super::{Transport,REPORT_SIZE,RawHid,ReportId,ReportIdTransport};
+
+impl RawHid for HidDevice{
+    fn write(&self,data:&[u8]) -> Result<usize,OpenDP100Error>{
+        match HidDevice::write(self,data){
+            Ok(size)=>{
+                Ok(size)
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("hid write failed",e))
+            }
+        }
+    }
+
+    fn read_timeout(&self,buf:&mut [u8],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        match HidDevice::read_timeout(self,buf,timeout_ms){
+            Ok(size)=>{
+                Ok(size)
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("hid read failed",e))
+            }
+        }
+    }
+}
+
+/// Transport backed by a `hidapi::HidDevice`
+pub struct HidTransport{
+    inner:ReportIdTransport<HidDevice>,
+}
+
+impl HidTransport{
+    /// Report id handling picked for the current platform, see `ReportId::Auto`
+    pub fn new(hid_device:HidDevice) -> Self{
+        Self::with_report_id(hid_device,ReportId::Auto)
+    }
+
+    pub fn with_report_id(hid_device:HidDevice,report_id:ReportId) -> Self{
+        Self{
+            inner:ReportIdTransport::new(hid_device,report_id)
+        }
+    }
+
+    pub fn device(&self) -> &HidDevice{
+        self.inner.device()
+    }
+
+    /// All DP100s hidapi can see, nothing is opened
+    pub fn enumerate() -> Result<Vec<DeviceDescriptor>,OpenDP100Error>{
+        let api = HidApi::new().map_err(|e| OpenDP100Error::driver("hidapi init failed",e))?;
+        Ok(Self::enumerate_with(&api))
+    }
+
+    /// Same as `enumerate`, reusing an existing `HidApi`
+    pub fn enumerate_with(api:&HidApi) -> Vec<DeviceDescriptor>{
+        api.device_list()
+            .filter(|device| device.vendor_id() == VID && device.product_id() == PID)
+            .map(|device| DeviceDescriptor{
+                backend:Backend::Hidapi,
+                path:device.path().to_string_lossy().to_string(),
+                serial:device.serial_number().map(str::to_string),
+                manufacturer:device.manufacturer_string().map(str::to_string),
+                product:device.product_string().map(str::to_string),
+                interface_number:Some(device.interface_number()).filter(|&n| n >= 0),
+            })
+            .collect()
+    }
+
+    /// Open the device at a HID path returned by `enumerate`
+    pub fn open_path(path:&str) -> Result<Self,OpenDP100Error>{
+        let api = HidApi::new().map_err(|e| OpenDP100Error::driver("hidapi init failed",e))?;
+        Self::open_path_with(&api,path)
+    }
+
+    pub fn open_path_with(api:&HidApi,path:&str) -> Result<Self,OpenDP100Error>{
+        let path = CString::new(path).map_err(|_| OpenDP100Error::InvalidParam("path contains a nul byte"))?;
+        match api.open_path(&path){
+            Ok(device)=>{
+                Ok(Self::new(device))
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("hid open failed",e))
+            }
+        }
+    }
+}
+
+impl Transport for HidTransport{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        self.inner.write(report)
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        self.inner.read(report,timeout_ms)
+    }
+}
@@ -0,0 +1,199 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+use crate::error::OpenDP100Error;
+use crate::backend::{Backend,DeviceDescriptor};
+use crate::{VID,PID};
+use super::{Transport,REPORT_SIZE};
+
+const SYSFS_ROOT: &str = "/sys";
+const DEV_ROOT: &str = "/dev";
+
+/// A DP100 found under `/sys/class/hidraw`
+#[derive(Debug,Clone,PartialEq)]
+pub struct HidrawDevice{
+    /// eg. `/dev/hidraw3`
+    pub path:PathBuf,
+    pub vendor_id:u16,
+    pub product_id:u16,
+    /// HID_NAME of the device, usually "manufacturer product"
+    pub name:Option<String>,
+    /// HID_UNIQ of the device, the USB serial
+    pub serial:Option<String>,
+    /// bInterfaceNumber of the USB interface the node belongs to
+    pub interface_number:Option<i32>,
+}
+
+impl From<HidrawDevice> for DeviceDescriptor{
+    fn from(device:HidrawDevice) -> Self{
+        DeviceDescriptor{
+            backend:Backend::Hidraw,
+            path:device.path.to_string_lossy().to_string(),
+            serial:device.serial,
+            manufacturer:None,
+            product:device.name,
+            interface_number:device.interface_number,
+        }
+    }
+}
+
+impl HidrawDevice{
+    /// Parse `<class dir>/<node>/device/uevent`
+    fn from_uevent(node:&str,uevent:&str) -> Option<Self>{
+        let mut ids = None;
+        let mut name = None;
+        let mut serial = None;
+
+        for line in uevent.lines(){
+            let (key,value) = match line.split_once('='){
+                Some(kv) => kv,
+                None => continue
+            };
+            match key{
+                // HID_ID=<bus>:<vendor>:<product>, all hex
+                "HID_ID" => {
+                    let mut parts = value.split(':').skip(1);
+                    let vendor = u32::from_str_radix(parts.next()?,16).ok()?;
+                    let product = u32::from_str_radix(parts.next()?,16).ok()?;
+                    ids = Some((vendor as u16,product as u16));
+                }
+                "HID_NAME" if !value.is_empty() => name = Some(value.to_string()),
+                "HID_UNIQ" if !value.is_empty() => serial = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        let (vendor_id,product_id) = ids?;
+        Some(HidrawDevice{
+            path:Path::new(DEV_ROOT).join(node),
+            vendor_id,
+            product_id,
+            name,
+            serial,
+            interface_number:None,
+        })
+    }
+}
+
+/// Linux hidraw transport in plain Rust, enabled by the `hidraw` feature.
+///
+/// Reports go straight to `/dev/hidrawN` with read/write, timeouts are
+/// done with poll(2). No hidapi C code is involved.
+pub struct HidrawTransport{
+    file:File,
+}
+
+impl HidrawTransport{
+    /// All DP100s visible in `/sys/class/hidraw`
+    pub fn enumerate() -> io::Result<Vec<HidrawDevice>>{
+        Self::enumerate_in(SYSFS_ROOT)
+    }
+
+    /// Same as `enumerate`, with sysfs mounted at `sysfs_root`
+    pub fn enumerate_in<P:AsRef<Path>>(sysfs_root:P) -> io::Result<Vec<HidrawDevice>>{
+        let class_dir = sysfs_root.as_ref().join("class").join("hidraw");
+        let mut nodes = Vec::new();
+        for entry in fs::read_dir(class_dir)?{
+            let entry = entry?;
+            let node = entry.file_name().to_string_lossy().to_string();
+            // a device may vanish or be unreadable while we look at it, skip it
+            let uevent = match fs::read_to_string(entry.path().join("device").join("uevent")){
+                Ok(uevent) => uevent,
+                Err(_) => continue
+            };
+            if let Some(mut device) = HidrawDevice::from_uevent(&node,&uevent){
+                if device.vendor_id == VID && device.product_id == PID{
+                    // device/ is the HID device, its parent the USB interface
+                    device.interface_number = fs::read_to_string(entry.path().join("device").join("..").join("bInterfaceNumber"))
+                        .ok()
+                        .and_then(|n| i32::from_str_radix(n.trim(),16).ok());
+                    nodes.push((node_number(&node),device));
+                }
+            }
+        }
+
+        // read_dir order is arbitrary, keep indices stable
+        nodes.sort_by_key(|(number,_)| *number);
+        Ok(nodes.into_iter().map(|(_,device)| device).collect())
+    }
+
+    /// Open the `device_idx`th DP100 returned by `enumerate`
+    pub fn open(device_idx:usize) -> Result<Self,OpenDP100Error>{
+        let devices = Self::enumerate().map_err(|e| OpenDP100Error::driver("reading sysfs failed",e))?;
+        let device = devices.get(device_idx).ok_or(OpenDP100Error::NotFound)?;
+        Self::open_path(&device.path)
+    }
+
+    pub fn open_path<P:AsRef<Path>>(path:P) -> Result<Self,OpenDP100Error>{
+        match OpenOptions::new().read(true).write(true).open(path){
+            Ok(file)=>{
+                Ok(Self{
+                    file
+                })
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("hidraw open failed",e))
+            }
+        }
+    }
+
+    /// Wait until a report can be read, false on timeout
+    fn poll(&self,timeout_ms:i32) -> Result<bool,OpenDP100Error>{
+        let mut fds = libc::pollfd{
+            fd:self.file.as_raw_fd(),
+            events:libc::POLLIN,
+            revents:0,
+        };
+        loop{
+            let res = unsafe { libc::poll(&mut fds,1,timeout_ms) };
+            if res < 0{
+                if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted{
+                    continue;
+                }
+                return Err(OpenDP100Error::transport("poll failed",io::Error::last_os_error()));
+            }
+            if res == 0{
+                return Ok(false);
+            }
+            if fds.revents & (libc::POLLERR | libc::POLLHUP | libc::POLLNVAL) != 0{
+                // unplugged
+                return Err(OpenDP100Error::link("hidraw node hung up"));
+            }
+            return Ok(true);
+        }
+    }
+}
+
+// "hidraw12" -> 12
+fn node_number(node:&str) -> u32{
+    node.trim_start_matches(|c:char| !c.is_ascii_digit()).parse().unwrap_or(u32::MAX)
+}
+
+impl Transport for HidrawTransport{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        match (&self.file).write_all(report){
+            Ok(_)=>{
+                Ok(())
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("hidraw write failed",e))
+            }
+        }
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        if !self.poll(timeout_ms)?{
+            return Ok(0);
+        }
+        match (&self.file).read(report){
+            Ok(size)=>{
+                Ok(size)
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("hidraw read failed",e))
+            }
+        }
+    }
+}
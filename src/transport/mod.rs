@@ -0,0 +1,47 @@
+use crate::error::OpenDP100Error;
+
+#[cfg(feature = "hidapi")]
+pub use hid::HidTransport;
+pub use channel::ChannelTransport;
+pub use report_id::{RawHid,ReportId,ReportIdTransport};
+pub use record::{Recorder,Replay,Record,Direction};
+#[cfg(feature = "libusb")]
+pub use usb::UsbTransport;
+#[cfg(all(feature = "hidraw", target_os = "linux"))]
+pub use hidraw::{HidrawTransport,HidrawDevice};
+
+#[cfg(feature = "hidapi")]
+mod hid;
+mod channel;
+mod report_id;
+mod record;
+#[cfg(feature = "libusb")]
+mod usb;
+#[cfg(all(feature = "hidraw", target_os = "linux"))]
+mod hidraw;
+
+/// Size of every USB interrupt report exchanged with a DP100
+pub const REPORT_SIZE: usize = 64;
+
+/// Moves raw 64 byte reports between the host and a DP100.
+///
+/// `OpenDP100` only deals with frames, everything below that
+/// (hidapi, in-memory channels ...) lives behind this trait.
+pub trait Transport {
+    /// Send one report to the device
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>;
+
+    /// Wait up to `timeout_ms` for one report from the device.
+    /// Returns the number of bytes received, 0 means timeout.
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>;
+}
+
+impl<T:Transport + ?Sized> Transport for Box<T>{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        (**self).write(report)
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        (**self).read(report, timeout_ms)
+    }
+}
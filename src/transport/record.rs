@@ -0,0 +1,249 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::error::OpenDP100Error;
+use super::{Transport,REPORT_SIZE};
+
+// Capture file layout, one report per line:
+//
+//   # open_dp100 capture
+//   <micros since start> W <128 hex chars>
+//   <micros since start> R <128 hex chars>
+//   <micros since start> R -
+//
+// `W` is host to device, `R` is device to host, `-` is a read that timed out.
+const CAPTURE_HEADER: &str = "# open_dp100 capture";
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum Direction{
+    Write,
+    Read,
+}
+
+/// One line of a capture
+#[derive(Debug,Clone)]
+pub struct Record{
+    pub micros:u64,
+    pub direction:Direction,
+    /// `None` for a read that timed out
+    pub report:Option<[u8;REPORT_SIZE]>,
+}
+
+impl Record{
+    fn write_to<W:Write>(&self,out:&mut W) -> io::Result<()>{
+        let dir = match self.direction {
+            Direction::Write => 'W',
+            Direction::Read => 'R',
+        };
+        write!(out,"{} {} ",self.micros,dir)?;
+        match &self.report {
+            Some(report) => {
+                for d in report.iter(){
+                    write!(out,"{:02x}",d)?;
+                }
+            }
+            None => {
+                write!(out,"-")?;
+            }
+        }
+        writeln!(out)
+    }
+
+    fn parse(line:&str) -> Option<Self>{
+        let mut parts = line.split_whitespace();
+        let micros = parts.next()?.parse().ok()?;
+        let direction = match parts.next()? {
+            "W" => Direction::Write,
+            "R" => Direction::Read,
+            _ => return None
+        };
+        let hex = parts.next()?;
+        if parts.next().is_some(){
+            return None;
+        }
+
+        let report = if hex == "-" {
+            None
+        } else {
+            if hex.len() != REPORT_SIZE * 2 {
+                return None;
+            }
+            let mut report = [0u8;REPORT_SIZE];
+            for (i,d) in report.iter_mut().enumerate(){
+                *d = u8::from_str_radix(hex.get(i*2..i*2+2)?,16).ok()?;
+            }
+            Some(report)
+        };
+
+        Some(Record{
+            micros,
+            direction,
+            report
+        })
+    }
+}
+
+/// Wraps another transport and logs every report that passes through it
+pub struct Recorder<T:Transport,W:Write = BufWriter<File>>{
+    inner:T,
+    out:Mutex<W>,
+    start:Instant,
+}
+
+impl<T:Transport> Recorder<T>{
+    /// Record into a new file at `path`, an existing file is truncated
+    pub fn create<P:AsRef<Path>>(inner:T,path:P) -> io::Result<Self>{
+        Self::new(inner,BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<T:Transport,W:Write> Recorder<T,W>{
+    pub fn new(inner:T,mut out:W) -> io::Result<Self>{
+        writeln!(out,"{}",CAPTURE_HEADER)?;
+        Ok(Self{
+            inner,
+            out:Mutex::new(out),
+            start:Instant::now(),
+        })
+    }
+
+    pub fn inner(&self) -> &T{
+        &self.inner
+    }
+
+    /// Stop recording, hand back the wrapped transport and the writer
+    pub fn into_parts(self) -> (T,W){
+        let out = self.out.into_inner().unwrap_or_else(|e| e.into_inner());
+        (self.inner,out)
+    }
+
+    fn log(&self,direction:Direction,report:Option<&[u8;REPORT_SIZE]>) -> Result<(),OpenDP100Error>{
+        let record = Record{
+            micros:self.start.elapsed().as_micros() as u64,
+            direction,
+            report:report.copied(),
+        };
+        let mut out = self.out.lock().map_err(|_| OpenDP100Error::link("capture lock poisoned"))?;
+        // flush every line so a crash still leaves a usable capture
+        match record.write_to(&mut *out).and_then(|_| out.flush()){
+            Ok(_)=>{
+                Ok(())
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("writing capture failed",e))
+            }
+        }
+    }
+}
+
+impl<T:Transport,W:Write> Transport for Recorder<T,W>{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        self.inner.write(report)?;
+        self.log(Direction::Write,Some(report))
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        let size = self.inner.read(report,timeout_ms)?;
+        if size == 0{
+            self.log(Direction::Read,None)?;
+        }else{
+            self.log(Direction::Read,Some(report))?;
+        }
+        Ok(size)
+    }
+}
+
+/// Plays a capture made by `Recorder` back as if it was the device.
+///
+/// Every write has to match the recorded request byte for byte,
+/// anything else (different bytes, unexpected order, running past the
+/// end of the capture) fails with `OpenDP100Error::Transport`.
+pub struct Replay{
+    records:Vec<Record>,
+    pos:Mutex<usize>,
+}
+
+impl Replay{
+    pub fn open<P:AsRef<Path>>(path:P) -> io::Result<Self>{
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn from_reader<R:BufRead>(reader:R) -> io::Result<Self>{
+        let mut records = Vec::new();
+        for (no,line) in reader.lines().enumerate(){
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#'){
+                continue;
+            }
+            match Record::parse(line){
+                Some(record) => records.push(record),
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("invalid capture line {}",no + 1)
+                    ));
+                }
+            }
+        }
+        Ok(Self::from_records(records))
+    }
+
+    pub fn from_records(records:Vec<Record>) -> Self{
+        Self{
+            records,
+            pos:Mutex::new(0)
+        }
+    }
+
+    pub fn records(&self) -> &[Record]{
+        &self.records
+    }
+
+    /// True once every recorded report has been served
+    pub fn is_finished(&self) -> bool{
+        *self.pos.lock().unwrap_or_else(|e| e.into_inner()) == self.records.len()
+    }
+
+    fn next(&self,direction:Direction) -> Result<&Record,OpenDP100Error>{
+        let mut pos = self.pos.lock().map_err(|_| OpenDP100Error::link("replay lock poisoned"))?;
+        match self.records.get(*pos){
+            Some(record) if record.direction == direction => {
+                *pos += 1;
+                Ok(record)
+            }
+            Some(_) => {
+                Err(OpenDP100Error::link("capture has a different direction here"))
+            }
+            None => {
+                Err(OpenDP100Error::link("ran past the end of the capture"))
+            }
+        }
+    }
+}
+
+impl Transport for Replay{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        let record = self.next(Direction::Write)?;
+        if record.report.as_ref() != Some(report){
+            return Err(OpenDP100Error::link("request differs from the capture"));
+        }
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],_timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        let record = self.next(Direction::Read)?;
+        match &record.report{
+            Some(data)=>{
+                *report = *data;
+                Ok(REPORT_SIZE)
+            }
+            None=>{
+                Ok(0)
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::error::OpenDP100Error;
+use super::{Transport,REPORT_SIZE};
+
+/// Byte level access to a HID device, the part of `hidapi::HidDevice`
+/// `ReportIdTransport` needs
+pub trait RawHid{
+    fn write(&self,data:&[u8]) -> Result<usize,OpenDP100Error>;
+    fn read_timeout(&self,buf:&mut [u8],timeout_ms:i32) -> Result<usize,OpenDP100Error>;
+}
+
+/// How reports are framed towards the HID driver.
+///
+/// The DP100 does not use numbered reports, but some hidapi builds
+/// (Windows) always treat the first byte written as the report id and
+/// drop it. Prefixing a report id byte keeps the frame intact.
+#[derive(Debug,Clone,Copy,PartialEq,Default)]
+pub enum ReportId{
+    /// `Prefix(0)` on Windows, `None` everywhere else
+    #[default]
+    Auto,
+    /// Reports go out as they are
+    None,
+    /// Put this byte in front of every report written
+    Prefix(u8),
+}
+
+impl ReportId{
+    /// The prefix byte this mode resolves to on the current platform
+    pub fn prefix(&self) -> Option<u8>{
+        match self{
+            ReportId::Auto => {
+                if cfg!(windows) { Some(0x00) } else { None }
+            }
+            ReportId::None => None,
+            ReportId::Prefix(id) => Some(*id),
+        }
+    }
+}
+
+/// Transport over a `RawHid` device that adds the report id on write and
+/// strips it again from reads that come back with one.
+pub struct ReportIdTransport<D:RawHid>{
+    device:D,
+    prefix:Option<u8>,
+}
+
+impl<D:RawHid> ReportIdTransport<D>{
+    pub fn new(device:D,report_id:ReportId) -> Self{
+        Self{
+            device,
+            prefix:report_id.prefix(),
+        }
+    }
+
+    pub fn device(&self) -> &D{
+        &self.device
+    }
+
+    /// Resolved prefix byte, `None` if reports are sent unchanged
+    pub fn prefix(&self) -> Option<u8>{
+        self.prefix
+    }
+}
+
+impl<D:RawHid> Transport for ReportIdTransport<D>{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        match self.prefix{
+            Some(id) => {
+                let mut buffer = [0u8;REPORT_SIZE + 1];
+                buffer[0] = id;
+                buffer[1..].copy_from_slice(report);
+                self.device.write(&buffer)?;
+            }
+            None => {
+                self.device.write(report)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        let mut buffer = [0u8;REPORT_SIZE + 1];
+        let size = self.device.read_timeout(&mut buffer,timeout_ms)?;
+
+        // only a read longer than a report can carry the id in front
+        let data = match self.prefix{
+            Some(id) if size == REPORT_SIZE + 1 && buffer[0] == id => &buffer[1..size],
+            _ => &buffer[0..size.min(REPORT_SIZE)],
+        };
+        report[0..data.len()].copy_from_slice(data);
+        Ok(data.len())
+    }
+}
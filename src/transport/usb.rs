@@ -0,0 +1,147 @@
+use std::time::Duration;
+
+use rusb::{Device, DeviceHandle, GlobalContext};
+
+use crate::error::OpenDP100Error;
+use crate::backend::{Backend,DeviceDescriptor};
+use crate::{VID,PID};
+use super::{Transport,REPORT_SIZE};
+
+const INTERFACE: u8 = 0;
+const ENDPOINT_IN: u8 = 0x81;
+const ENDPOINT_OUT: u8 = 0x01;
+const WRITE_TIME_OUT_MS: u64 = 1000;
+
+/// Transport talking to the interrupt endpoints through libusb (`rusb`),
+/// enabled by the `libusb` feature.
+///
+/// hidapi and the OS HID driver are bypassed completely, so there is no
+/// report id byte to take care of.
+pub struct UsbTransport{
+    handle:DeviceHandle<GlobalContext>,
+}
+
+impl UsbTransport{
+    /// All DP100s on the bus. Each one is opened for a moment to read
+    /// serial, manufacturer and product, they stay `None` where that is
+    /// not allowed (eg. missing udev permissions). No interface is claimed.
+    pub fn enumerate() -> Result<Vec<DeviceDescriptor>,OpenDP100Error>{
+        Ok(Self::devices()?.iter().map(|device| {
+            let (serial,manufacturer,product) = read_strings(device);
+            DeviceDescriptor{
+                backend:Backend::Libusb,
+                path:device_path(device),
+                serial,
+                manufacturer,
+                product,
+                interface_number:Some(INTERFACE as i32),
+            }
+        }).collect())
+    }
+
+    /// Open the `device_idx`th DP100 found on the bus
+    pub fn open(device_idx:usize) -> Result<Self,OpenDP100Error>{
+        let devices = Self::devices()?;
+        let device = devices.get(device_idx).ok_or(OpenDP100Error::NotFound)?;
+        Self::open_device(device)
+    }
+
+    /// Open the DP100 at `<bus>:<address>`, as returned by `enumerate`
+    pub fn open_path(path:&str) -> Result<Self,OpenDP100Error>{
+        let devices = Self::devices()?;
+        let device = devices.iter()
+            .find(|device| device_path(device) == path)
+            .ok_or(OpenDP100Error::NotFound)?;
+        Self::open_device(device)
+    }
+
+    fn devices() -> Result<Vec<Device<GlobalContext>>,OpenDP100Error>{
+        let devices = rusb::devices().map_err(|e| OpenDP100Error::driver("libusb device list failed",e))?;
+        Ok(devices.iter()
+            .filter(|device| {
+                match device.device_descriptor(){
+                    Ok(desc) => desc.vendor_id() == VID && desc.product_id() == PID,
+                    Err(_) => false
+                }
+            })
+            .collect())
+    }
+
+    fn open_device(device:&Device<GlobalContext>) -> Result<Self,OpenDP100Error>{
+        let handle = device.open().map_err(|e| OpenDP100Error::transport("libusb open failed",e))?;
+        Self::new(handle)
+    }
+
+    /// Claim interface 0 of an already opened device
+    pub fn new(handle:DeviceHandle<GlobalContext>) -> Result<Self,OpenDP100Error>{
+        // the OS HID driver owns the interface, take it over for as long as we hold it.
+        // not supported on every platform, claim_interface tells if it matters
+        let _ = handle.set_auto_detach_kernel_driver(true);
+        match handle.claim_interface(INTERFACE){
+            Ok(_)=>{
+                Ok(Self{
+                    handle
+                })
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("claim interface failed",e))
+            }
+        }
+    }
+}
+
+fn device_path(device:&Device<GlobalContext>) -> String{
+    format!("{:03}:{:03}",device.bus_number(),device.address())
+}
+
+// serial, manufacturer and product string descriptors, the handle is closed again on return
+fn read_strings(device:&Device<GlobalContext>) -> (Option<String>,Option<String>,Option<String>){
+    let (desc,handle) = match (device.device_descriptor(),device.open()){
+        (Ok(desc),Ok(handle)) => (desc,handle),
+        _ => return (None,None,None)
+    };
+    (
+        handle.read_serial_number_string_ascii(&desc).ok(),
+        handle.read_manufacturer_string_ascii(&desc).ok(),
+        handle.read_product_string_ascii(&desc).ok(),
+    )
+}
+
+impl Drop for UsbTransport{
+    fn drop(&mut self){
+        let _ = self.handle.release_interface(INTERFACE);
+    }
+}
+
+impl Transport for UsbTransport{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        match self.handle.write_interrupt(ENDPOINT_OUT,report,Duration::from_millis(WRITE_TIME_OUT_MS)){
+            Ok(_)=>{
+                Ok(())
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("interrupt write failed",e))
+            }
+        }
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        // libusb waits forever on a zero timeout, hidapi returns at once
+        let timeout = match timeout_ms {
+            t if t < 0 => Duration::ZERO,
+            0 => Duration::from_millis(1),
+            t => Duration::from_millis(t as u64)
+        };
+        match self.handle.read_interrupt(ENDPOINT_IN,report,timeout){
+            Ok(size)=>{
+                Ok(size)
+            }
+            Err(rusb::Error::Timeout)=>{
+                Ok(0)
+            }
+            Err(e)=>{
+                Err(OpenDP100Error::transport("interrupt read failed",e))
+            }
+        }
+    }
+}
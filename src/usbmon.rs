@@ -0,0 +1,299 @@
+//! Reader for Linux usbmon captures (`tcpdump -i usbmonN -w file`, or
+//! Wireshark), in pcap or pcapng format, that picks out DP100 reports.
+
+use std::collections::HashSet;
+use std::convert::TryInto;
+use std::io::{self, Read};
+
+use crate::transport::REPORT_SIZE;
+use crate::{PID, VID};
+
+// pcap link types for usbmon, the mmapped one has 16 more header bytes
+const LINKTYPE_USB_LINUX: u32 = 189;
+const LINKTYPE_USB_LINUX_MMAPPED: u32 = 220;
+
+const PCAP_MAGIC_US: u32 = 0xa1b2c3d4;
+const PCAP_MAGIC_NS: u32 = 0xa1b23c4d;
+const PCAPNG_SHB: u32 = 0x0a0d0d0a;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1a2b3c4d;
+const PCAPNG_IDB: u32 = 1;
+const PCAPNG_SPB: u32 = 3;
+const PCAPNG_EPB: u32 = 6;
+
+const XFER_INTERRUPT: u8 = 1;
+const XFER_CONTROL: u8 = 2;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum UsbDirection{
+    /// Host to device, an OUT endpoint
+    Out,
+    /// Device to host, an IN endpoint
+    In,
+}
+
+/// One usbmon event that carries data
+#[derive(Debug,Clone,PartialEq)]
+pub struct UsbPacket{
+    /// Seconds since the epoch, as stored in the capture
+    pub timestamp:f64,
+    pub bus:u16,
+    pub device:u8,
+    /// Endpoint address, bit 7 set for IN
+    pub endpoint:u8,
+    pub transfer_type:u8,
+    /// 'S' submit, 'C' complete, 'E' error
+    pub event:u8,
+    pub data:Vec<u8>,
+}
+
+impl UsbPacket{
+    pub fn direction(&self) -> UsbDirection{
+        if self.endpoint & 0x80 != 0 { UsbDirection::In } else { UsbDirection::Out }
+    }
+
+    /// Parse a usbmon packet, `mmapped` for link type 220
+    fn parse(buf:&[u8],mmapped:bool,big_endian:bool) -> Option<Self>{
+        let header_len = if mmapped { 64 } else { 48 };
+        if buf.len() < header_len{
+            return None;
+        }
+        let u16_at = |o:usize| {
+            let b = [buf[o],buf[o+1]];
+            if big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) }
+        };
+        let u32_at = |o:usize| {
+            let b = [buf[o],buf[o+1],buf[o+2],buf[o+3]];
+            if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) }
+        };
+        let sec = u32_at(if big_endian { 20 } else { 16 }) as f64;
+        let usec = u32_at(24) as f64;
+        let len_cap = u32_at(36) as usize;
+        let data = buf.get(header_len..header_len + len_cap).unwrap_or(&buf[header_len..]);
+
+        Some(UsbPacket{
+            timestamp:sec + usec / 1e6,
+            event:buf[8],
+            transfer_type:buf[9],
+            endpoint:buf[10],
+            device:buf[11],
+            bus:u16_at(12),
+            data:data.to_vec(),
+        })
+    }
+
+    /// The data part of an interrupt transfer, OUT data is in the submit
+    /// event, IN data in the completion
+    fn interrupt_report(&self) -> Option<&[u8]>{
+        if self.transfer_type != XFER_INTERRUPT || self.data.is_empty(){
+            return None;
+        }
+        let event = match self.direction(){
+            UsbDirection::Out => b'S',
+            UsbDirection::In => b'C',
+        };
+        if self.event == event { Some(&self.data) } else { None }
+    }
+
+    /// `(vid,pid)` if this completes a GET_DESCRIPTOR(device) request
+    fn device_descriptor(&self) -> Option<(u16,u16)>{
+        if self.transfer_type != XFER_CONTROL || self.event != b'C' || self.endpoint != 0x80{
+            return None;
+        }
+        let d = &self.data;
+        // bLength 18, bDescriptorType 1
+        if d.len() < 12 || d[0] != 18 || d[1] != 1{
+            return None;
+        }
+        Some((u16::from_le_bytes([d[8],d[9]]),u16::from_le_bytes([d[10],d[11]])))
+    }
+}
+
+/// A report sent to or from a DP100, found in a capture
+#[derive(Debug,Clone,PartialEq)]
+pub struct CapturedReport{
+    pub timestamp:f64,
+    pub direction:UsbDirection,
+    pub bus:u16,
+    pub device:u8,
+    pub data:Vec<u8>,
+}
+
+impl CapturedReport{
+    /// The report padded or cut to `REPORT_SIZE`, ready for `deserialize_in_frame`
+    pub fn report(&self) -> [u8;REPORT_SIZE]{
+        let mut report = [0u8;REPORT_SIZE];
+        let n = self.data.len().min(REPORT_SIZE);
+        report[..n].copy_from_slice(&self.data[..n]);
+        report
+    }
+}
+
+fn invalid(msg:&str) -> io::Error{
+    io::Error::new(io::ErrorKind::InvalidData,msg.to_string())
+}
+
+struct Cursor<'a>{
+    buf:&'a [u8],
+    pos:usize,
+    big_endian:bool,
+}
+
+impl<'a> Cursor<'a>{
+    fn u32(&mut self) -> Option<u32>{
+        let b:[u8;4] = self.buf.get(self.pos..self.pos + 4)?.try_into().ok()?;
+        self.pos += 4;
+        Some(if self.big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    }
+
+    fn bytes(&mut self,len:usize) -> Option<&'a [u8]>{
+        let b = self.buf.get(self.pos..self.pos.checked_add(len)?)?;
+        self.pos += len;
+        Some(b)
+    }
+}
+
+/// Every usbmon packet in a pcap or pcapng capture
+pub fn read_packets<R:Read>(mut reader:R) -> io::Result<Vec<UsbPacket>>{
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    if buf.len() < 4{
+        return Err(invalid("capture too short"));
+    }
+    let magic = [buf[0],buf[1],buf[2],buf[3]];
+    if u32::from_le_bytes(magic) == PCAPNG_SHB{
+        read_pcapng(&buf)
+    }else{
+        read_pcap(&buf)
+    }
+}
+
+fn read_pcap(buf:&[u8]) -> io::Result<Vec<UsbPacket>>{
+    let magic = [buf[0],buf[1],buf[2],buf[3]];
+    let big_endian = match u32::from_le_bytes(magic){
+        PCAP_MAGIC_US | PCAP_MAGIC_NS => false,
+        _ => match u32::from_be_bytes(magic){
+            PCAP_MAGIC_US | PCAP_MAGIC_NS => true,
+            _ => return Err(invalid("not a pcap or pcapng file"))
+        }
+    };
+    let mut cur = Cursor{ buf, pos:20, big_endian };
+    let link_type = cur.u32().ok_or_else(|| invalid("truncated pcap header"))?;
+    let mmapped = match link_type{
+        LINKTYPE_USB_LINUX => false,
+        LINKTYPE_USB_LINUX_MMAPPED => true,
+        _ => return Err(invalid("not a usbmon capture"))
+    };
+
+    let mut packets = Vec::new();
+    while cur.pos < buf.len(){
+        // ts_sec, ts_usec, incl_len, orig_len
+        let record = (|| {
+            cur.bytes(8)?;
+            let incl_len = cur.u32()? as usize;
+            cur.u32()?;
+            cur.bytes(incl_len)
+        })().ok_or_else(|| invalid("truncated pcap record"))?;
+        if let Some(packet) = UsbPacket::parse(record,mmapped,big_endian){
+            packets.push(packet);
+        }
+    }
+    Ok(packets)
+}
+
+fn read_pcapng(buf:&[u8]) -> io::Result<Vec<UsbPacket>>{
+    let mut packets = Vec::new();
+    // link type of each interface of the current section
+    let mut interfaces:Vec<u32> = Vec::new();
+    let mut cur = Cursor{ buf, pos:0, big_endian:false };
+
+    while cur.pos < buf.len(){
+        let start = cur.pos;
+        let block_type = cur.u32().ok_or_else(|| invalid("truncated pcapng block"))?;
+        if block_type == PCAPNG_SHB{
+            // the byte order magic decides how the rest of the section is read
+            let magic:[u8;4] = buf.get(start + 8..start + 12).ok_or_else(|| invalid("truncated pcapng section"))?.try_into().unwrap();
+            cur.big_endian = u32::from_be_bytes(magic) == PCAPNG_BYTE_ORDER_MAGIC;
+            interfaces.clear();
+        }
+        let total_len = cur.u32().ok_or_else(|| invalid("truncated pcapng block"))? as usize;
+        if total_len < 12 || !total_len.is_multiple_of(4){
+            return Err(invalid("bad pcapng block length"));
+        }
+        let body = cur.bytes(total_len - 12).ok_or_else(|| invalid("truncated pcapng block"))?;
+        cur.u32();
+
+        let mut body = Cursor{ buf:body, pos:0, big_endian:cur.big_endian };
+        match block_type{
+            PCAPNG_IDB => {
+                let link = body.u32().ok_or_else(|| invalid("truncated interface block"))?;
+                let link = if cur.big_endian { link >> 16 } else { link & 0xffff };
+                interfaces.push(link);
+            }
+            PCAPNG_EPB | PCAPNG_SPB => {
+                let (interface,data) = if block_type == PCAPNG_EPB{
+                    (|| {
+                        let interface = body.u32()? as usize;
+                        body.bytes(8)?;
+                        let cap_len = body.u32()? as usize;
+                        body.u32()?;
+                        Some((interface,body.bytes(cap_len)?))
+                    })()
+                }else{
+                    (|| {
+                        let orig_len = body.u32()? as usize;
+                        let rest = body.buf.len() - body.pos;
+                        Some((0,body.bytes(orig_len.min(rest))?))
+                    })()
+                }.ok_or_else(|| invalid("truncated packet block"))?;
+
+                let mmapped = match interfaces.get(interface){
+                    Some(&LINKTYPE_USB_LINUX) => false,
+                    Some(&LINKTYPE_USB_LINUX_MMAPPED) => true,
+                    // not usb, or no such interface
+                    _ => continue
+                };
+                if let Some(packet) = UsbPacket::parse(data,mmapped,cur.big_endian){
+                    packets.push(packet);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(packets)
+}
+
+/// The interrupt reports sent to and from DP100s.
+///
+/// DP100s are found by the device descriptors read during enumeration.
+/// When the capture started after the DP100 was plugged in there are
+/// none, then any 64 byte interrupt report starting with a DP100 frame
+/// header (0xfb out, 0xfa in) is taken.
+pub fn dp100_reports(packets:&[UsbPacket]) -> Vec<CapturedReport>{
+    let known:HashSet<(u16,u8)> = packets.iter()
+        .filter(|p| p.device_descriptor() == Some((VID,PID)))
+        .map(|p| (p.bus,p.device))
+        .collect();
+
+    packets.iter()
+        .filter_map(|p| {
+            let data = p.interrupt_report()?;
+            let direction = p.direction();
+            let is_dp100 = if known.is_empty(){
+                let header = if direction == UsbDirection::Out { 0xfb } else { 0xfa };
+                data.len() == REPORT_SIZE && data[0] == header
+            }else{
+                known.contains(&(p.bus,p.device))
+            };
+            if !is_dp100{
+                return None;
+            }
+            Some(CapturedReport{
+                timestamp:p.timestamp,
+                direction,
+                bus:p.bus,
+                device:p.device,
+                data:data.to_vec(),
+            })
+        })
+        .collect()
+}
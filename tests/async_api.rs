@@ -0,0 +1,146 @@
+#![cfg(feature = "async")]
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use open_dp100::{AsyncOpenDP100, Millivolts, OpenDP100, OpenDP100Error, OutputState, RetryPolicy, SessionConfig, Simulator, Transport, REPORT_SIZE};
+
+fn simulated() -> AsyncOpenDP100<Simulator>{
+    AsyncOpenDP100::new(OpenDP100::with_transport(Simulator::new()))
+}
+
+// a simulated DP100 that needs `delay` for every request
+struct Slow{
+    device:Simulator,
+    delay:Duration,
+}
+
+impl Transport for Slow{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        thread::sleep(self.delay);
+        self.device.write(report)
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        self.device.read(report,timeout_ms)
+    }
+}
+
+// never answers, every read waits out its timeout
+struct Silent;
+
+impl Transport for Silent{
+    fn write(&self,_report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        Ok(())
+    }
+
+    fn read(&self,_report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        thread::sleep(Duration::from_millis(timeout_ms.max(0) as u64));
+        Ok(0)
+    }
+}
+
+#[tokio::test]
+async fn calls_reach_the_simulator(){
+    let device = simulated();
+    assert_eq!(device.device_info().await.unwrap().model(),"DP100");
+    assert_eq!(device.basic_info().await.unwrap().vin,Millivolts(20000));
+    device.switch_config(2).await.unwrap();
+    assert_eq!(device.current_basic_set().await.unwrap().index,2);
+    device.set_output_on(OutputState::On).await.unwrap();
+    assert_eq!(device.basic_set(2).await.unwrap().state,OutputState::On);
+}
+
+#[tokio::test]
+async fn concurrent_calls_do_not_mix(){
+    let device = simulated();
+    let other = device.clone();
+    let (info,system,set) = tokio::join!(device.basic_info(),other.sys_info(),device.basic_set(5));
+    assert_eq!(info.unwrap().vin,Millivolts(20000));
+    assert_eq!(system.unwrap().blk_lev,4);
+    assert_eq!(set.unwrap().index,5);
+}
+
+#[tokio::test]
+async fn silent_device_is_bounded_by_the_session_config(){
+    let device = AsyncOpenDP100::new(OpenDP100::with_transport(Silent));
+    device.set_config(SessionConfig{
+        timeout:Duration::from_millis(50),
+        retry:RetryPolicy::never(),
+        ..SessionConfig::default()
+    }).await.unwrap();
+    assert_eq!(device.config().await.unwrap().timeout,Duration::from_millis(50));
+
+    let start = Instant::now();
+    assert!(matches!(device.basic_info().await,Err(OpenDP100Error::Timeout{..})));
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[tokio::test]
+async fn dropped_call_is_cancelled_and_frees_the_device(){
+    let device = AsyncOpenDP100::new(OpenDP100::with_transport(Silent));
+    device.set_config(SessionConfig{
+        timeout:Duration::from_secs(5),
+        retry:RetryPolicy::never(),
+        ..SessionConfig::default()
+    }).await.unwrap();
+
+    let abandoned = tokio::time::timeout(Duration::from_millis(20),device.basic_info()).await;
+    assert!(abandoned.is_err());
+
+    // the cancelled call lets go of the device long before its 5 s are up
+    let start = Instant::now();
+    let short = SessionConfig{
+        timeout:Duration::from_millis(50),
+        retry:RetryPolicy::never(),
+        ..SessionConfig::default()
+    };
+    assert!(matches!(device.using(short).sys_info().await,Err(OpenDP100Error::Timeout{..})));
+    assert!(start.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn queued_call_dropped_before_it_runs_never_reaches_the_device(){
+    let device = AsyncOpenDP100::new(OpenDP100::with_transport(Slow{
+        device:Simulator::new(),
+        delay:Duration::from_millis(100),
+    }));
+    let busy = device.clone();
+    let first = tokio::spawn(async move { busy.basic_info().await });
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // waits behind `first` for the lock and is given up on meanwhile
+    let abandoned = tokio::time::timeout(Duration::from_millis(10),device.set_output_on(OutputState::On)).await;
+    assert!(abandoned.is_err());
+    first.await.unwrap().unwrap();
+
+    assert_eq!(device.current_basic_set().await.unwrap().state,OutputState::Off);
+}
+
+#[tokio::test]
+async fn using_overrides_the_config_for_one_call(){
+    let device = AsyncOpenDP100::new(OpenDP100::with_transport(Silent));
+    let patient = SessionConfig{
+        timeout:Duration::from_secs(5),
+        retry:RetryPolicy::never(),
+        ..SessionConfig::default()
+    };
+    device.set_config(patient.clone()).await.unwrap();
+
+    let start = Instant::now();
+    let short = SessionConfig{
+        timeout:Duration::from_millis(50),
+        ..patient.clone()
+    };
+    assert!(matches!(device.using(short).basic_info().await,Err(OpenDP100Error::Timeout{..})));
+    assert!(start.elapsed() < Duration::from_secs(1));
+    assert_eq!(device.config().await.unwrap(),patient);
+}
+
+#[tokio::test]
+async fn using_reaches_the_simulator(){
+    let device = simulated();
+    let config = SessionConfig::default();
+    device.using(config.clone()).switch_config(3).await.unwrap();
+    assert_eq!(device.using(config).current_basic_set().await.unwrap().index,3);
+}
@@ -0,0 +1,111 @@
+use std::thread;
+
+use open_dp100::{deserialize_out_frame, serialize_in_frame, BasicSet, ChannelTransport, Frame, Milliamps, Millivolts, OpCode, OpenDP100, OpenDP100Error, OutMode, OutputState, Transport, WorkState, REPORT_SIZE};
+
+// the device end of the pair: reads one request, checks it and sends `reply`
+fn expect(device:&ChannelTransport,op_code:OpCode,data:&[u8],reply:&[u8]) -> Frame{
+    let mut report = [0u8;REPORT_SIZE];
+    assert_eq!(device.read(&mut report,1000).unwrap(),REPORT_SIZE);
+    let mut request = Frame::empty();
+    deserialize_out_frame(&report,&mut request).unwrap();
+    assert_eq!(request.op_code,op_code);
+    assert_eq!(request.data(),data);
+
+    let mut frame = Frame::new(op_code,reply).unwrap();
+    frame.serial_num = request.serial_num;
+    let mut buffer = [0u8;REPORT_SIZE];
+    serialize_in_frame(&frame,&mut buffer);
+    device.write(&buffer).unwrap();
+    request
+}
+
+const BASIC_INFO:[u8;16] = [0x20,0x4e,0x88,0x13,0xe8,0x03,0x38,0x4a,0xfa,0x00,0xfa,0x00,0x88,0x13,0x01,0x00];
+const PRESET_3:[u8;10] = [0x03,0x00,0x88,0x13,0xe8,0x03,0x24,0x77,0xba,0x13];
+
+#[test]
+fn basic_info_over_a_channel(){
+    let (host,device) = ChannelTransport::pair();
+    let script = thread::spawn(move || {
+        expect(&device,OpCode::BasicInfo,&[],&BASIC_INFO);
+    });
+
+    let info = OpenDP100::with_transport(host).basic_info().unwrap();
+    script.join().unwrap();
+    assert_eq!((info.vin,info.vout,info.iout),(Millivolts(20000),Millivolts(5000),Milliamps(1000)));
+    assert_eq!((info.out_mode,info.work_st),(OutMode::Cv,WorkState::Normal));
+}
+
+#[test]
+fn every_request_gets_a_new_serial(){
+    let (host,device) = ChannelTransport::pair();
+    let script = thread::spawn(move || {
+        let first = expect(&device,OpCode::BasicInfo,&[],&BASIC_INFO);
+        let second = expect(&device,OpCode::BasicInfo,&[],&BASIC_INFO);
+        (first.serial_num,second.serial_num)
+    });
+
+    let dp100 = OpenDP100::with_transport(host);
+    dp100.basic_info().unwrap();
+    dp100.basic_info().unwrap();
+    let (first,second) = script.join().unwrap();
+    assert_ne!(first,0);
+    assert_ne!(first,second);
+}
+
+#[test]
+fn switch_config_reads_then_activates(){
+    let (host,device) = ChannelTransport::pair();
+    let script = thread::spawn(move || {
+        expect(&device,OpCode::BasicSet,&[3],&PRESET_3);
+        // 0xa0: modify and activate preset 3
+        let mut update = PRESET_3;
+        update[0] = 0xa3;
+        expect(&device,OpCode::BasicSet,&update,&[1]);
+    });
+
+    OpenDP100::with_transport(host).switch_config(3).unwrap();
+    script.join().unwrap();
+}
+
+#[test]
+fn set_output_on_skips_the_write_when_already_on(){
+    let (host,device) = ChannelTransport::pair();
+    let script = thread::spawn(move || {
+        let mut current = PRESET_3;
+        current[1] = 1;
+        expect(&device,OpCode::BasicSet,&[0x80],&current);
+    });
+
+    OpenDP100::with_transport(host).set_output_on(OutputState::On).unwrap();
+    script.join().unwrap();
+}
+
+#[test]
+fn update_basic_set_reports_a_refusal(){
+    let (host,device) = ChannelTransport::pair();
+    let set = BasicSet{
+        index:3,
+        state:OutputState::Off,
+        vo_set:Millivolts(5000),
+        io_set:Milliamps(1000),
+        ovp_set:Millivolts(30500),
+        ocp_set:Milliamps(5050),
+    };
+    let script = thread::spawn(move || {
+        let mut update = PRESET_3;
+        update[0] = 0x23;
+        expect(&device,OpCode::BasicSet,&update,&[0]);
+    });
+
+    let err = OpenDP100::with_transport(host).update_basic_set(&set,false).unwrap_err();
+    script.join().unwrap();
+    assert!(matches!(err,OpenDP100Error::DeviceFailure{op_code:OpCode::BasicSet}));
+}
+
+#[test]
+fn closed_device_end_is_a_transport_error(){
+    let (host,device) = ChannelTransport::pair();
+    drop(device);
+    let err = OpenDP100::with_transport(host).basic_info().unwrap_err();
+    assert!(matches!(err,OpenDP100Error::Transport{..}));
+}
@@ -0,0 +1,34 @@
+use open_dp100::{FrameError, Operational, OutputState};
+
+// a layout the DP100 does not have, to cover what the built in payloads do not use,
+// derived through the re-exports of this crate
+#[derive(Debug,Clone,PartialEq,Operational)]
+#[operational(crate = "open_dp100")]
+struct Probe{
+    state:OutputState,
+    #[be]
+    big:u16,
+    #[le]
+    little:i32,
+    tag:[u8;3],
+    // field names that are also names the generated code uses
+    #[le]
+    end:u16,
+    data:i8,
+}
+
+#[test]
+fn derived_layout(){
+    assert_eq!(Probe::SIZE,13);
+
+    let probe = Probe{ state:OutputState::On, big:0x1234, little:-2, tag:*b"abc", end:0x5678, data:-1 };
+    let data = probe.to_data();
+    assert_eq!(data,[0x01,0x12,0x34,0xfe,0xff,0xff,0xff,b'a',b'b',b'c',0x78,0x56,0xff]);
+    assert_eq!(Probe::from_data(&data).unwrap(),probe);
+}
+
+#[test]
+fn derived_length_check(){
+    assert_eq!(Probe::from_data(&[0u8;12]).err(),Some(FrameError::InvalidPayload));
+    assert_eq!(Probe::from_data(&[0u8;14]).err(),Some(FrameError::InvalidPayload));
+}
@@ -0,0 +1,144 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use open_dp100::{deserialize_out_frame, serialize_in_frame, BasicSet, ErrorClass, Frame, Milliamps, Millivolts, OpCode, OpenDP100, OpenDP100Error, OutputState, Recorder, RetryPolicy, SessionConfig, Simulator, Transport, REPORT_SIZE};
+
+// answers every request with whatever `reply` builds from it
+struct Scripted<F:Fn(&Frame) -> [u8;REPORT_SIZE]>{
+    reply:F,
+    pending:Mutex<Option<[u8;REPORT_SIZE]>>,
+}
+
+impl<F:Fn(&Frame) -> [u8;REPORT_SIZE]> Scripted<F>{
+    fn new(reply:F) -> Self{
+        Scripted{
+            reply,
+            pending:Mutex::new(None),
+        }
+    }
+}
+
+impl<F:Fn(&Frame) -> [u8;REPORT_SIZE]> Transport for Scripted<F>{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        let mut request = Frame::empty();
+        deserialize_out_frame(report,&mut request).unwrap();
+        *self.pending.lock().unwrap() = Some((self.reply)(&request));
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],_timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        match self.pending.lock().unwrap().take(){
+            Some(reply) => {
+                *report = reply;
+                Ok(REPORT_SIZE)
+            }
+            None => Ok(0)
+        }
+    }
+}
+
+fn reply(request:&Frame,op_code:OpCode,data:&[u8]) -> [u8;REPORT_SIZE]{
+    let mut frame = Frame::new(op_code,data).unwrap();
+    frame.serial_num = request.serial_num;
+    let mut buffer = [0u8;REPORT_SIZE];
+    serialize_in_frame(&frame,&mut buffer);
+    buffer
+}
+
+fn once() -> SessionConfig{
+    SessionConfig{
+        retry:RetryPolicy::never(),
+        ..SessionConfig::default()
+    }
+}
+
+#[test]
+fn crc_error_keeps_raw_bytes(){
+    let device = OpenDP100::with_transport(Scripted::new(|request| {
+        let mut buffer = reply(request,OpCode::SystemInfo,&[4,0x04,0x29,0x20,0x03,2]);
+        buffer[10] ^= 0xff;
+        buffer
+    }));
+    let err = device.sys_info().unwrap_err();
+    match &err{
+        OpenDP100Error::Crc{op_code,raw} => {
+            assert_eq!(*op_code,OpCode::SystemInfo);
+            assert_eq!(raw.len(),REPORT_SIZE);
+            assert_eq!(raw[0],0xfa);
+        }
+        e => panic!("unexpected {:?}",e)
+    }
+    assert_eq!(err.class(),Some(ErrorClass::Corrupt));
+    assert!(err.to_string().contains("crc"));
+}
+
+#[test]
+fn unexpected_op_code_names_both(){
+    let device = OpenDP100::with_transport(Scripted::new(|request| reply(request,OpCode::SystemInfo,&[4,0x04,0x29,0x20,0x03,2])));
+    let err = device.using(&once()).basic_info().unwrap_err();
+    match &err{
+        OpenDP100Error::UnexpectedOpCode{expected,received,raw} => {
+            assert_eq!(*expected,OpCode::BasicInfo);
+            assert_eq!(*received,OpCode::SystemInfo);
+            assert_eq!(raw.len(),6 + 6);
+        }
+        e => panic!("unexpected {:?}",e)
+    }
+    assert_eq!(err.to_string().matches("BasicInfo").count(),1);
+}
+
+#[test]
+fn short_payload_is_a_length_error(){
+    let device = OpenDP100::with_transport(Scripted::new(|request| reply(request,OpCode::BasicSet,&[0x01])));
+    match device.basic_set(1).unwrap_err(){
+        OpenDP100Error::PayloadLength{op_code,expected,received,..} => {
+            assert_eq!(op_code,OpCode::BasicSet);
+            assert_eq!(expected,10);
+            assert_eq!(received,1);
+        }
+        e => panic!("unexpected {:?}",e)
+    }
+}
+
+#[test]
+fn device_failure_is_reported(){
+    let device = OpenDP100::with_transport(Simulator::new());
+    let set = BasicSet{
+        index:10,
+        state:OutputState::Off,
+        vo_set:Millivolts(5000),
+        io_set:Milliamps(1000),
+        ovp_set:Millivolts(30500),
+        ocp_set:Milliamps(5050),
+    };
+    assert!(matches!(device.update_basic_set(&set,false),Err(OpenDP100Error::DeviceFailure{op_code:OpCode::BasicSet})));
+}
+
+#[test]
+fn timeout_names_the_request(){
+    let device = OpenDP100::with_transport(Scripted::new(|_| [0u8;REPORT_SIZE]));
+    let err = device.device_info().unwrap_err();
+    assert!(matches!(err,OpenDP100Error::Timeout{op_code:Some(OpCode::DeviceInfo)}));
+}
+
+// takes every byte but can never get them to disk
+struct Full;
+
+impl Write for Full{
+    fn write(&mut self,buf:&[u8]) -> io::Result<usize>{
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()>{
+        Err(io::Error::other("disk full"))
+    }
+}
+
+#[test]
+fn transport_error_chains_its_source(){
+    let device = OpenDP100::with_transport(Recorder::new(Simulator::new(),Full).unwrap());
+    let err = device.basic_info().unwrap_err();
+    assert!(matches!(err,OpenDP100Error::Transport{..}));
+    assert_eq!(err.source().unwrap().to_string(),"disk full");
+}
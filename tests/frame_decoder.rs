@@ -0,0 +1,123 @@
+use open_dp100::{serialize_in_frame, serialize_out_frame, Frame, FrameDecoder, FrameError, OpCode};
+
+// header to crc of a device to host frame
+fn reply(op_code:OpCode,serial_num:u8,data:&[u8]) -> Vec<u8>{
+    let mut frame = Frame::new(op_code,data).unwrap();
+    frame.serial_num = serial_num;
+    let mut buffer = [0u8;64];
+    serialize_in_frame(&frame,&mut buffer);
+    buffer[..data.len() + 6].to_vec()
+}
+
+const SYSTEM_INFO:[u8;6] = [4,0x04,0x29,0x20,0x03,2];
+
+#[test]
+fn whole_frame(){
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&reply(OpCode::SystemInfo,7,&SYSTEM_INFO));
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!((frame.op_code,frame.serial_num),(OpCode::SystemInfo,7));
+    assert_eq!(frame.data(),&SYSTEM_INFO);
+    assert!(decoder.next_frame().is_none());
+    assert_eq!((decoder.pending(),decoder.discarded()),(0,0));
+}
+
+#[test]
+fn garbage_before_the_header_is_skipped_and_counted(){
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&[0x11,0x22,0x33]);
+    decoder.push(&reply(OpCode::SystemInfo,1,&SYSTEM_INFO));
+    assert_eq!(decoder.next_frame().unwrap().unwrap().op_code,OpCode::SystemInfo);
+    assert_eq!(decoder.discarded(),3);
+}
+
+#[test]
+fn zero_padding_is_not_counted(){
+    let mut decoder = FrameDecoder::new();
+    let mut report = reply(OpCode::SystemInfo,1,&SYSTEM_INFO);
+    report.resize(64,0);
+    decoder.push(&report);
+    decoder.push(&report);
+    assert!(decoder.next_frame().unwrap().is_ok());
+    assert!(decoder.next_frame().unwrap().is_ok());
+    assert!(decoder.next_frame().is_none());
+    assert_eq!((decoder.pending(),decoder.discarded()),(0,0));
+}
+
+#[test]
+fn frame_split_across_reports(){
+    let bytes = reply(OpCode::SystemInfo,1,&SYSTEM_INFO);
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&bytes[..2]);
+    assert!(decoder.next_frame().is_none());
+    decoder.push(&bytes[2..7]);
+    assert!(decoder.next_frame().is_none());
+    assert_eq!(decoder.pending(),7);
+    decoder.push(&bytes[7..]);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().data(),&SYSTEM_INFO);
+    assert_eq!(decoder.pending(),0);
+}
+
+#[test]
+fn oversize_len_is_rejected_then_skipped(){
+    let mut decoder = FrameDecoder::new();
+    // len 0x3b is one byte more than a report can carry
+    decoder.push(&[0xfa,0x40,0x01,0x3b]);
+    decoder.push(&reply(OpCode::SystemInfo,2,&SYSTEM_INFO));
+    assert_eq!(decoder.next_frame().unwrap().unwrap_err(),FrameError::DataTooLong);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().serial_num,2);
+    assert_eq!(decoder.discarded(),4);
+}
+
+#[test]
+fn crc_failure_then_valid_frame(){
+    let mut broken = reply(OpCode::SystemInfo,1,&SYSTEM_INFO);
+    let last = broken.len() - 1;
+    broken[last] ^= 0xff;
+
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&broken);
+    decoder.push(&reply(OpCode::SystemInfo,2,&SYSTEM_INFO));
+    assert_eq!(decoder.next_frame().unwrap().unwrap_err(),FrameError::InvalidCrc);
+    let frame = decoder.next_frame().unwrap().unwrap();
+    assert_eq!(frame.serial_num,2);
+    assert_eq!(decoder.discarded(),broken.len());
+    assert!(decoder.next_frame().is_none());
+}
+
+#[test]
+fn unknown_op_code_drops_the_whole_frame(){
+    let mut bytes = reply(OpCode::SystemInfo,1,&SYSTEM_INFO);
+    // 0x99 is no op code, fix up the crc so only the op code is wrong
+    bytes[1] = 0x99;
+    let len = bytes.len();
+    let crc = crc16::State::<crc16::MODBUS>::calculate(&bytes[..len - 2]);
+    bytes[len - 2..].copy_from_slice(&crc.to_le_bytes());
+
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&bytes);
+    decoder.push(&reply(OpCode::SystemInfo,2,&SYSTEM_INFO));
+    assert_eq!(decoder.next_frame().unwrap().unwrap_err(),FrameError::InvalidOpCode);
+    assert_eq!(decoder.next_frame().unwrap().unwrap().serial_num,2);
+    assert_eq!(decoder.discarded(),len);
+}
+
+#[test]
+fn host_frames_with_their_own_header(){
+    let mut frame = Frame::new(OpCode::BasicSet,&[0x80]).unwrap();
+    frame.serial_num = 3;
+    let mut buffer = [0u8;64];
+    serialize_out_frame(&frame,&mut buffer);
+
+    // a device to host decoder sees no header at all
+    let mut decoder = FrameDecoder::new();
+    decoder.push(&buffer[..7]);
+    assert!(decoder.next_frame().is_none());
+    assert_eq!(decoder.discarded(),7);
+
+    let mut decoder = FrameDecoder::with_header(0xfb);
+    decoder.push(&buffer);
+    let decoded = decoder.next_frame().unwrap().unwrap();
+    assert_eq!((decoded.op_code,decoded.serial_num),(OpCode::BasicSet,3));
+    assert_eq!(decoded.data(),&[0x80]);
+}
@@ -0,0 +1,71 @@
+#![cfg(all(feature = "hidraw", target_os = "linux"))]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use open_dp100::{Backend, DeviceDescriptor, HidrawTransport};
+
+// builds <root>/class/hidraw/<node>/device/uevent like the kernel does
+fn fake_sysfs(name:&str,nodes:&[(&str,&str)]) -> PathBuf{
+    let root = std::env::temp_dir().join(format!("open_dp100_sysfs_{}_{}",name,std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (node,uevent) in nodes{
+        let dir = root.join("class").join("hidraw").join(node).join("device");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("uevent"),uevent).unwrap();
+    }
+    root
+}
+
+#[test]
+fn enumerate_finds_only_dp100_in_node_order(){
+    let root = fake_sysfs("order",&[
+        ("hidraw10","DRIVER=hid-generic\nHID_ID=0003:00002E3C:0000AF01\nHID_NAME=ALIENTEK ATK-MDP100\nHID_UNIQ=B\n"),
+        ("hidraw2","DRIVER=hid-generic\nHID_ID=0003:00002E3C:0000AF01\nHID_NAME=ALIENTEK ATK-MDP100\nHID_UNIQ=A\n"),
+        ("hidraw0","DRIVER=hid-generic\nHID_ID=0003:0000046D:0000C52B\nHID_NAME=Logitech USB Receiver\n"),
+    ]);
+
+    let devices = HidrawTransport::enumerate_in(&root).unwrap();
+    assert_eq!(devices.len(),2);
+    assert_eq!(devices[0].path,Path::new("/dev/hidraw2"));
+    assert_eq!(devices[0].serial.as_deref(),Some("A"));
+    assert_eq!(devices[1].path,Path::new("/dev/hidraw10"));
+    assert_eq!(devices[1].name.as_deref(),Some("ALIENTEK ATK-MDP100"));
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn enumerate_skips_broken_entries(){
+    let root = fake_sysfs("broken",&[
+        ("hidraw0","HID_ID=garbage\n"),
+        ("hidraw1","HID_ID=0003:00002E3C:0000AF01\nHID_UNIQ=\n"),
+    ]);
+    fs::create_dir_all(root.join("class").join("hidraw").join("hidraw2")).unwrap();
+
+    let devices = HidrawTransport::enumerate_in(&root).unwrap();
+    assert_eq!(devices.len(),1);
+    assert_eq!(devices[0].serial,None);
+
+    fs::remove_dir_all(root).unwrap();
+}
+
+#[test]
+fn descriptor_carries_serial_and_interface(){
+    let root = fake_sysfs("descriptor",&[
+        ("hidraw4","HID_ID=0003:00002E3C:0000AF01\nHID_NAME=ALIENTEK ATK-MDP100\nHID_UNIQ=ABC123\n"),
+    ]);
+    // device/ is a symlink into the usb interface in real sysfs, the fake
+    // keeps bInterfaceNumber next to it
+    fs::write(root.join("class").join("hidraw").join("hidraw4").join("bInterfaceNumber"),"00\n").unwrap();
+
+    let devices = HidrawTransport::enumerate_in(&root).unwrap();
+    let descriptor = DeviceDescriptor::from(devices[0].clone());
+    assert_eq!(descriptor.backend,Backend::Hidraw);
+    assert_eq!(descriptor.path,"/dev/hidraw4");
+    assert_eq!(descriptor.serial.as_deref(),Some("ABC123"));
+    assert_eq!(descriptor.product.as_deref(),Some("ALIENTEK ATK-MDP100"));
+    assert_eq!(descriptor.interface_number,Some(0));
+
+    fs::remove_dir_all(root).unwrap();
+}
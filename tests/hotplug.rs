@@ -0,0 +1,130 @@
+#![cfg(any(feature = "hidapi", all(feature = "hidraw", target_os = "linux"), feature = "libusb"))]
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use open_dp100::{Backend, DeviceDescriptor, Enumerator, HotplugEvent, HotplugWatcher, OpenDP100Error};
+
+// hands out one scripted device list per enumerate call
+struct Script{
+    rounds:Mutex<VecDeque<Result<Vec<DeviceDescriptor>,OpenDP100Error>>>,
+}
+
+impl Script{
+    fn new(rounds:Vec<Result<Vec<DeviceDescriptor>,OpenDP100Error>>) -> Self{
+        Script{
+            rounds:Mutex::new(rounds.into())
+        }
+    }
+}
+
+impl Enumerator for Script{
+    fn enumerate(&self) -> Result<Vec<DeviceDescriptor>,OpenDP100Error>{
+        self.rounds.lock().unwrap().pop_front().expect("script exhausted")
+    }
+}
+
+fn dp100(serial:&str,path:&str) -> DeviceDescriptor{
+    DeviceDescriptor{
+        backend:Backend::default(),
+        path:path.to_string(),
+        serial:Some(serial.to_string()),
+        manufacturer:None,
+        product:None,
+        interface_number:Some(0),
+    }
+}
+
+#[test]
+fn reports_attach_and_detach(){
+    let a = dp100("A","1-1");
+    let b = dp100("B","1-2");
+    let mut watcher = HotplugWatcher::new(Script::new(vec![
+        Ok(vec![a.clone()]),
+        Ok(vec![a.clone(),b.clone()]),
+        Ok(vec![a.clone(),b.clone()]),
+        Ok(vec![b.clone()]),
+        Ok(vec![]),
+    ]));
+
+    assert_eq!(watcher.poll().unwrap(),vec![HotplugEvent::Attached(a.clone())]);
+    assert_eq!(watcher.poll().unwrap(),vec![HotplugEvent::Attached(b.clone())]);
+    assert_eq!(watcher.poll().unwrap(),vec![]);
+    assert_eq!(watcher.devices().len(),2);
+    assert_eq!(watcher.poll().unwrap(),vec![HotplugEvent::Detached(a)]);
+    assert_eq!(watcher.poll().unwrap(),vec![HotplugEvent::Detached(b)]);
+}
+
+#[test]
+fn moved_device_is_detached_then_attached(){
+    let old = dp100("A","1-1");
+    let new = dp100("A","1-3");
+    let mut watcher = HotplugWatcher::new(Script::new(vec![
+        Ok(vec![old.clone()]),
+        Ok(vec![new.clone()]),
+    ]));
+
+    watcher.poll().unwrap();
+    assert_eq!(watcher.poll().unwrap(),vec![HotplugEvent::Detached(old),HotplugEvent::Attached(new)]);
+}
+
+#[test]
+fn failed_enumeration_keeps_devices(){
+    let a = dp100("A","1-1");
+    let mut watcher = HotplugWatcher::new(Script::new(vec![
+        Ok(vec![a.clone()]),
+        Err(OpenDP100Error::Driver{context:"scripted failure",source:None}),
+        Ok(vec![a.clone()]),
+    ]));
+
+    watcher.poll().unwrap();
+    assert!(watcher.poll().is_err());
+    assert_eq!(watcher.devices(),&[a][..]);
+    assert_eq!(watcher.poll().unwrap(),vec![]);
+}
+
+#[test]
+fn spawned_watcher_sends_events(){
+    let a = dp100("A","1-1");
+    let rounds = Mutex::new(VecDeque::from(vec![vec![a.clone()]]));
+    // the bus stays empty once the script is done
+    let watcher = HotplugWatcher::new(move || Ok(rounds.lock().unwrap().pop_front().unwrap_or_default()));
+    let handle = watcher.spawn(Duration::from_millis(10));
+    let timeout = Duration::from_secs(2);
+    assert_eq!(handle.events().recv_timeout(timeout).unwrap(),HotplugEvent::Attached(a.clone()));
+    assert_eq!(handle.events().recv_timeout(timeout).unwrap(),HotplugEvent::Detached(a));
+    handle.stop();
+}
+
+#[test]
+fn stop_ends_a_quiet_watcher_without_waiting_out_the_interval(){
+    let watcher = HotplugWatcher::new(|| Ok(Vec::new()));
+    let handle = watcher.spawn(Duration::from_secs(60));
+    let start = Instant::now();
+    handle.stop();
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[test]
+fn dropped_handle_ends_the_thread(){
+    let polls = Arc::new(AtomicUsize::new(0));
+    let watcher = {
+        let polls = polls.clone();
+        HotplugWatcher::new(move || {
+            polls.fetch_add(1, Ordering::SeqCst);
+            Ok(Vec::new())
+        })
+    };
+    let handle = watcher.spawn(Duration::from_millis(5));
+    thread::sleep(Duration::from_millis(30));
+    drop(handle);
+
+    // one poll may still be under way when the handle goes
+    thread::sleep(Duration::from_millis(30));
+    let after_drop = polls.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(polls.load(Ordering::SeqCst),after_drop);
+}
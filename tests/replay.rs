@@ -0,0 +1,107 @@
+use std::io::ErrorKind;
+
+use open_dp100::{deserialize_in_frame, deserialize_out_frame, Frame, Millivolts, OpCode, OpenDP100, OpenDP100Error, Recorder, Replay, Simulator, Transport, REPORT_SIZE};
+use open_dp100::transport::{Direction, Record};
+
+const SESSION:&str = "tests/captures/session.txt";
+
+// the calls tests/captures/session.txt was recorded with
+fn run_session<T:Transport>(device:&OpenDP100<T>) -> Result<(),OpenDP100Error>{
+    let info = device.device_info()?;
+    assert_eq!(info.model(),"DP100");
+    assert_eq!(info.year,2023);
+    assert_eq!(device.basic_info()?.vin,Millivolts(20000));
+    assert_eq!(device.sys_info()?.blk_lev,4);
+    assert_eq!(device.current_basic_set()?.index,0);
+    device.switch_config(2)?;
+    device.basic_info()?;
+    Ok(())
+}
+
+#[test]
+fn recorded_session_replays(){
+    let replay = Replay::open(SESSION).unwrap();
+    let device = OpenDP100::with_transport(replay);
+    run_session(&device).unwrap();
+    assert!(device.transport().is_finished());
+}
+
+#[test]
+fn captured_frames_still_decode(){
+    let replay = Replay::open(SESSION).unwrap();
+    let mut replies = Vec::new();
+    for record in replay.records(){
+        let report = match &record.report{
+            Some(report) => report,
+            None => continue
+        };
+        let mut frame = Frame::empty();
+        match record.direction{
+            Direction::Write => deserialize_out_frame(report,&mut frame).unwrap(),
+            Direction::Read => {
+                deserialize_in_frame(report,&mut frame).unwrap();
+                replies.push(frame.op_code);
+            }
+        }
+    }
+    assert_eq!(replies,[
+        OpCode::DeviceInfo,
+        OpCode::BasicInfo,
+        OpCode::SystemInfo,
+        OpCode::BasicSet,
+        OpCode::BasicSet,
+        OpCode::BasicSet,
+        OpCode::BasicInfo,
+    ]);
+}
+
+#[test]
+fn different_request_fails(){
+    let device = OpenDP100::with_transport(Replay::open(SESSION).unwrap());
+    // the capture starts with device_info
+    assert!(matches!(device.sys_info(),Err(OpenDP100Error::Transport{..})));
+}
+
+#[test]
+fn wrong_direction_fails(){
+    let replay = Replay::from_records(vec![Record{
+        micros:0,
+        direction:Direction::Read,
+        report:None,
+    }]);
+    assert!(matches!(replay.write(&[0u8;REPORT_SIZE]),Err(OpenDP100Error::Transport{..})));
+}
+
+#[test]
+fn running_past_the_end_fails(){
+    let device = OpenDP100::with_transport(Replay::open(SESSION).unwrap());
+    run_session(&device).unwrap();
+    assert!(matches!(device.basic_info(),Err(OpenDP100Error::Transport{..})));
+}
+
+#[test]
+fn recorder_output_replays(){
+    let recorder = Recorder::new(Simulator::new(),Vec::new()).unwrap();
+    let device = OpenDP100::with_transport(recorder);
+    run_session(&device).unwrap();
+    let (_,capture) = device.into_transport().into_parts();
+
+    let replay = Replay::from_reader(&capture[..]).unwrap();
+    let recorded = Replay::open(SESSION).unwrap();
+    assert_eq!(replay.records().len(),recorded.records().len());
+    for (a,b) in replay.records().iter().zip(recorded.records()){
+        assert_eq!((a.direction,a.report),(b.direction,b.report));
+    }
+
+    let device = OpenDP100::with_transport(replay);
+    run_session(&device).unwrap();
+    assert!(device.transport().is_finished());
+}
+
+#[test]
+fn broken_capture_line_is_rejected(){
+    let capture = "# open_dp100 capture\n0 W fb30\n";
+    let err = Replay::from_reader(capture.as_bytes()).err().unwrap();
+    assert_eq!(err.kind(),ErrorKind::InvalidData);
+    assert!(err.to_string().contains("line 2"));
+}
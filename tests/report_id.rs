@@ -0,0 +1,86 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use open_dp100::{OpenDP100, OpenDP100Error, RawHid, ReportId, ReportIdTransport, Transport, REPORT_SIZE};
+
+// records every write and serves canned reads
+#[derive(Default)]
+struct MockHid{
+    written:RefCell<Vec<Vec<u8>>>,
+    reads:RefCell<VecDeque<Vec<u8>>>,
+}
+
+impl RawHid for MockHid{
+    fn write(&self,data:&[u8]) -> Result<usize,OpenDP100Error>{
+        self.written.borrow_mut().push(data.to_vec());
+        Ok(data.len())
+    }
+
+    fn read_timeout(&self,buf:&mut [u8],_timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        match self.reads.borrow_mut().pop_front(){
+            Some(data) => {
+                buf[0..data.len()].copy_from_slice(&data);
+                Ok(data.len())
+            }
+            None => Ok(0)
+        }
+    }
+}
+
+// fb 30 <serial 01> 00 <crc>, a BASIC_INFO request
+fn basic_info_request() -> Vec<u8>{
+    let mut report = vec![0u8;REPORT_SIZE];
+    report[0..6].copy_from_slice(&[0xfb,0x30,0x01,0x00,0x30,0x9f]);
+    report
+}
+
+#[test]
+fn prefix_is_written_in_front_of_the_report(){
+    let transport = ReportIdTransport::new(MockHid::default(),ReportId::Prefix(0x00));
+    let device = OpenDP100::with_transport(transport);
+    let _ = device.basic_info();
+
+    let written = device.transport().device().written.borrow();
+    let mut expected = vec![0x00];
+    expected.extend(basic_info_request());
+    assert_eq!(written.len(),1);
+    assert_eq!(written[0],expected);
+}
+
+#[test]
+fn no_prefix_writes_the_report_unchanged(){
+    let transport = ReportIdTransport::new(MockHid::default(),ReportId::None);
+    let device = OpenDP100::with_transport(transport);
+    let _ = device.basic_info();
+
+    let written = device.transport().device().written.borrow();
+    assert_eq!(written.len(),1);
+    assert_eq!(written[0],basic_info_request());
+}
+
+#[test]
+fn auto_resolves_per_platform(){
+    if cfg!(windows){
+        assert_eq!(ReportId::Auto.prefix(),Some(0x00));
+    }else{
+        assert_eq!(ReportId::Auto.prefix(),None);
+    }
+}
+
+#[test]
+fn prefix_is_stripped_from_reads(){
+    let mock = MockHid::default();
+    let mut with_id = vec![0x00];
+    with_id.extend((1..=REPORT_SIZE as u8).collect::<Vec<u8>>());
+    mock.reads.borrow_mut().push_back(with_id);
+    // a driver that already stripped the id hands over a plain report
+    mock.reads.borrow_mut().push_back((1..=REPORT_SIZE as u8).collect());
+
+    let transport = ReportIdTransport::new(mock,ReportId::Prefix(0x00));
+    for _ in 0..2{
+        let mut report = [0u8;REPORT_SIZE];
+        assert_eq!(transport.read(&mut report,0).unwrap(),REPORT_SIZE);
+        assert_eq!(report[0],1);
+        assert_eq!(report[REPORT_SIZE - 1],REPORT_SIZE as u8);
+    }
+}
@@ -0,0 +1,226 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use open_dp100::{OpenDP100Error, ReconnectPolicy, ResilientOpenDP100, RetryPolicy, SessionConfig, Simulator, Transport, REPORT_SIZE};
+
+// a simulated DP100 whose cable can be pulled, once pulled the handle stays dead
+struct Cable{
+    device:Simulator,
+    pulled:Arc<AtomicBool>,
+}
+
+impl Transport for Cable{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        if self.pulled.load(Ordering::SeqCst){
+            return Err(pulled());
+        }
+        self.device.write(report)
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        if self.pulled.load(Ordering::SeqCst){
+            return Err(pulled());
+        }
+        self.device.read(report,timeout_ms)
+    }
+}
+
+fn pulled() -> OpenDP100Error{
+    OpenDP100Error::Transport{
+        context:"cable pulled",
+        source:None,
+    }
+}
+
+fn fast() -> ReconnectPolicy{
+    ReconnectPolicy{
+        max_attempts:2,
+        delay:Duration::from_millis(0),
+        timeouts_before_reconnect:2,
+    }
+}
+
+#[test]
+fn reconnects_after_the_link_drops(){
+    let pulled = Arc::new(AtomicBool::new(false));
+    let connects = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let pulled = pulled.clone();
+        let connects = connects.clone();
+        move || {
+            // only the first connect gets the cable that is pulled later
+            let pulled = if connects.fetch_add(1, Ordering::SeqCst) == 0{
+                pulled.clone()
+            }else{
+                Arc::new(AtomicBool::new(false))
+            };
+            Ok(Cable{ device:Simulator::new(), pulled })
+        }
+    };
+
+    let device = ResilientOpenDP100::new(connector).unwrap().with_policy(fast());
+    device.basic_info().unwrap();
+    assert_eq!(device.reconnect_count(),0);
+
+    pulled.store(true, Ordering::SeqCst);
+    assert_eq!(device.device_info().unwrap().year,2023);
+    assert_eq!(device.reconnect_count(),1);
+    assert_eq!(connects.load(Ordering::SeqCst),2);
+}
+
+#[test]
+fn gives_up_after_max_attempts(){
+    let connects = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let connects = connects.clone();
+        move || {
+            if connects.fetch_add(1, Ordering::SeqCst) == 0{
+                Ok(Cable{ device:Simulator::new(), pulled:Arc::new(AtomicBool::new(true)) })
+            }else{
+                // the unit never comes back
+                Err(OpenDP100Error::NotFound)
+            }
+        }
+    };
+
+    let device = ResilientOpenDP100::new(connector).unwrap().with_policy(fast());
+    assert!(matches!(device.basic_info(),Err(OpenDP100Error::Transport{..})));
+    assert_eq!(device.reconnect_count(),0);
+    assert_eq!(connects.load(Ordering::SeqCst),3);
+}
+
+#[test]
+fn invalid_param_is_not_retried(){
+    let connects = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let connects = connects.clone();
+        move || {
+            connects.fetch_add(1, Ordering::SeqCst);
+            Ok(Simulator::new())
+        }
+    };
+
+    let device = ResilientOpenDP100::new(connector).unwrap().with_policy(fast());
+    assert!(matches!(device.basic_set(11),Err(OpenDP100Error::InvalidParam(_))));
+    assert_eq!(connects.load(Ordering::SeqCst),1);
+}
+
+#[test]
+fn config_survives_a_reconnect(){
+    let pulled = Arc::new(AtomicBool::new(false));
+    let connector = {
+        let pulled = pulled.clone();
+        let first = AtomicBool::new(true);
+        move || {
+            let pulled = if first.swap(false, Ordering::SeqCst){
+                pulled.clone()
+            }else{
+                Arc::new(AtomicBool::new(false))
+            };
+            Ok(Cable{ device:Simulator::new(), pulled })
+        }
+    };
+    let config = SessionConfig{
+        timeout:Duration::from_millis(20),
+        retry:RetryPolicy::never(),
+        ..SessionConfig::default()
+    };
+
+    let device = ResilientOpenDP100::new(connector).unwrap().with_policy(fast()).with_config(config.clone());
+    assert_eq!(device.call(|device| Ok(device.config().clone())).unwrap(),config);
+
+    pulled.store(true, Ordering::SeqCst);
+    let after = device.call(|device| device.basic_info().map(|_| device.config().clone())).unwrap();
+    assert_eq!(after,config);
+    assert_eq!(device.reconnect_count(),1);
+}
+
+// a simulated DP100 that swallows the replies to the first `drop` requests
+struct Sluggish{
+    device:Simulator,
+    drop:AtomicUsize,
+}
+
+impl Sluggish{
+    fn new(drop:usize) -> Self{
+        Sluggish{ device:Simulator::new(), drop:AtomicUsize::new(drop) }
+    }
+}
+
+impl Transport for Sluggish{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        self.device.write(report)?;
+        if self.drop.load(Ordering::SeqCst) > 0{
+            self.drop.fetch_sub(1, Ordering::SeqCst);
+            let mut reply = [0u8;REPORT_SIZE];
+            self.device.read(&mut reply,0)?;
+        }
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        self.device.read(report,timeout_ms)
+    }
+}
+
+#[test]
+fn single_timeout_keeps_the_link(){
+    let connects = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let connects = connects.clone();
+        move || {
+            connects.fetch_add(1, Ordering::SeqCst);
+            Ok(Sluggish::new(1))
+        }
+    };
+
+    let device = ResilientOpenDP100::new(connector).unwrap().with_policy(fast());
+    assert!(matches!(device.basic_info(),Err(OpenDP100Error::Timeout{..})));
+    device.basic_info().unwrap();
+    assert_eq!(device.reconnect_count(),0);
+    assert_eq!(connects.load(Ordering::SeqCst),1);
+}
+
+#[test]
+fn timeouts_in_a_row_reconnect(){
+    let connects = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let connects = connects.clone();
+        move || {
+            // the first handle never answers again, the next one is fine
+            let drop = if connects.fetch_add(1, Ordering::SeqCst) == 0 { usize::MAX } else { 0 };
+            Ok(Sluggish::new(drop))
+        }
+    };
+
+    let device = ResilientOpenDP100::new(connector).unwrap().with_policy(fast());
+    assert!(matches!(device.basic_info(),Err(OpenDP100Error::Timeout{..})));
+    assert_eq!(device.reconnect_count(),0);
+    // the second timeout in a row gives up on the handle
+    device.basic_info().unwrap();
+    assert_eq!(device.reconnect_count(),1);
+    assert_eq!(connects.load(Ordering::SeqCst),2);
+}
+
+#[test]
+fn timeouts_never_reconnect_when_turned_off(){
+    let connects = Arc::new(AtomicUsize::new(0));
+    let connector = {
+        let connects = connects.clone();
+        move || {
+            connects.fetch_add(1, Ordering::SeqCst);
+            Ok(Sluggish::new(usize::MAX))
+        }
+    };
+
+    let policy = ReconnectPolicy{
+        timeouts_before_reconnect:0,
+        ..fast()
+    };
+    let device = ResilientOpenDP100::new(connector).unwrap().with_policy(policy);
+    for _ in 0..5{
+        assert!(matches!(device.basic_info(),Err(OpenDP100Error::Timeout{..})));
+    }
+    assert_eq!(connects.load(Ordering::SeqCst),1);
+}
@@ -0,0 +1,198 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use open_dp100::{serialize_in_frame, Backoff, ErrorClass, Frame, OpCode, OpenDP100, OpenDP100Error, RetryPolicy, SessionConfig, Simulator, Transport, REPORT_SIZE};
+
+// a simulated DP100 that swallows the first `drop` replies
+struct Lossy{
+    device:Simulator,
+    drop:AtomicUsize,
+    writes:AtomicUsize,
+}
+
+impl Lossy{
+    fn new(drop:usize) -> Self{
+        Lossy{
+            device:Simulator::new(),
+            drop:AtomicUsize::new(drop),
+            writes:AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Transport for Lossy{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.device.write(report)?;
+        if self.drop.load(Ordering::SeqCst) > 0{
+            self.drop.fetch_sub(1, Ordering::SeqCst);
+            let mut reply = [0u8;REPORT_SIZE];
+            self.device.read(&mut reply,0)?;
+        }
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        self.device.read(report,timeout_ms)
+    }
+}
+
+fn retry_timeouts(max_attempts:u32) -> SessionConfig{
+    SessionConfig{
+        timeout:Duration::from_millis(10),
+        retry:RetryPolicy{
+            max_attempts,
+            backoff:Backoff::None,
+            retry_on:vec![ErrorClass::Timeout],
+        },
+        ..SessionConfig::default()
+    }
+}
+
+#[test]
+fn timeout_is_not_retried_by_default(){
+    let device = OpenDP100::with_transport(Lossy::new(1));
+    assert!(matches!(device.basic_info(),Err(OpenDP100Error::Timeout{op_code:Some(_)})));
+    assert_eq!(device.transport().writes.load(Ordering::SeqCst),1);
+}
+
+#[test]
+fn instance_config_retries_timeouts(){
+    let mut device = OpenDP100::with_transport(Lossy::new(2));
+    device.set_config(retry_timeouts(3));
+    device.basic_info().unwrap();
+    assert_eq!(device.transport().writes.load(Ordering::SeqCst),3);
+}
+
+#[test]
+fn gives_up_after_max_attempts(){
+    let mut device = OpenDP100::with_transport(Lossy::new(5));
+    device.set_config(retry_timeouts(3));
+    assert!(device.basic_info().is_err());
+    assert_eq!(device.transport().writes.load(Ordering::SeqCst),3);
+}
+
+#[test]
+fn per_call_config_overrides_instance(){
+    let device = OpenDP100::with_transport(Lossy::new(1));
+    let patient = retry_timeouts(2);
+    device.using(&patient).device_info().unwrap();
+    assert_eq!(device.config(),&SessionConfig::default());
+}
+
+#[test]
+fn exponential_backoff_is_capped(){
+    let backoff = Backoff::Exponential{
+        initial:Duration::from_millis(10),
+        max:Duration::from_millis(50),
+    };
+    assert_eq!(backoff.delay(1),Duration::from_millis(10));
+    assert_eq!(backoff.delay(2),Duration::from_millis(20));
+    assert_eq!(backoff.delay(3),Duration::from_millis(40));
+    assert_eq!(backoff.delay(4),Duration::from_millis(50));
+    assert_eq!(backoff.delay(40),Duration::from_millis(50));
+}
+
+// answers every read at once with `report`, before any request when `eager`
+struct Chatty{
+    report:[u8;REPORT_SIZE],
+    eager:bool,
+    written:AtomicBool,
+}
+
+impl Chatty{
+    fn new(report:[u8;REPORT_SIZE],eager:bool) -> Self{
+        Chatty{
+            report,
+            eager,
+            written:AtomicBool::new(false),
+        }
+    }
+}
+
+impl Transport for Chatty{
+    fn write(&self,_report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        self.written.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],_timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        if !self.eager && !self.written.load(Ordering::SeqCst){
+            return Ok(0);
+        }
+        *report = self.report;
+        Ok(REPORT_SIZE)
+    }
+}
+
+// a BASIC_INFO reply nobody asked for
+fn stale_reply() -> [u8;REPORT_SIZE]{
+    let mut frame = Frame::new(OpCode::BasicInfo,&[0u8;16]).unwrap();
+    frame.serial_num = 0xee;
+    let mut report = [0u8;REPORT_SIZE];
+    serialize_in_frame(&frame,&mut report);
+    report
+}
+
+fn short_timeout() -> SessionConfig{
+    SessionConfig{
+        timeout:Duration::from_millis(50),
+        retry:RetryPolicy::never(),
+        ..SessionConfig::default()
+    }
+}
+
+#[test]
+fn endless_stale_replies_time_out(){
+    let device = OpenDP100::with_transport(Chatty::new(stale_reply(),false));
+    let start = Instant::now();
+    assert!(matches!(device.using(&short_timeout()).basic_info(),Err(OpenDP100Error::Timeout{op_code:Some(OpCode::BasicInfo)})));
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn endless_garbage_times_out(){
+    let device = OpenDP100::with_transport(Chatty::new([0x55;REPORT_SIZE],false));
+    let start = Instant::now();
+    assert!(matches!(device.using(&short_timeout()).basic_info(),Err(OpenDP100Error::Timeout{..})));
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn flush_of_an_endless_stream_times_out(){
+    let device = OpenDP100::with_transport(Chatty::new(stale_reply(),true));
+    let start = Instant::now();
+    assert!(matches!(device.using(&short_timeout()).basic_info(),Err(OpenDP100Error::Timeout{..})));
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert!(!device.transport().written.load(Ordering::SeqCst));
+}
+
+#[test]
+fn cancelled_request_is_not_written(){
+    let device = OpenDP100::with_transport(Lossy::new(0));
+    let cancel = AtomicBool::new(true);
+    let config = SessionConfig::default();
+    assert!(matches!(device.using(&config).cancel_on(&cancel).basic_info(),Err(OpenDP100Error::Cancelled{op_code:OpCode::BasicInfo})));
+    assert_eq!(device.transport().writes.load(Ordering::SeqCst),0);
+}
+
+#[test]
+fn cancel_stops_waiting_and_retrying(){
+    let device = OpenDP100::with_transport(Lossy::new(usize::MAX));
+    let cancel = AtomicBool::new(false);
+    let config = SessionConfig{
+        timeout:Duration::from_millis(500),
+        ..retry_timeouts(10)
+    };
+    let start = Instant::now();
+    let res = std::thread::scope(|scope| {
+        scope.spawn(|| {
+            std::thread::sleep(Duration::from_millis(50));
+            cancel.store(true, Ordering::SeqCst);
+        });
+        device.using(&config).cancel_on(&cancel).basic_info()
+    });
+    assert!(matches!(res,Err(OpenDP100Error::Cancelled{..})));
+    assert!(start.elapsed() < Duration::from_millis(400));
+    assert_eq!(device.transport().writes.load(Ordering::SeqCst),1);
+}
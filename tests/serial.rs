@@ -0,0 +1,120 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use open_dp100::{deserialize_out_frame, serialize_in_frame, Frame, Millivolts, OpCode, OpenDP100, OpenDP100Error, SessionConfig, Simulator, Transport, REPORT_SIZE};
+
+// queues whatever `reply` builds for each request, on top of anything queued by hand
+struct Scripted<F:Fn(&Frame) -> Vec<[u8;REPORT_SIZE]>>{
+    reply:F,
+    queue:Mutex<VecDeque<[u8;REPORT_SIZE]>>,
+}
+
+impl<F:Fn(&Frame) -> Vec<[u8;REPORT_SIZE]>> Scripted<F>{
+    fn new(reply:F) -> Self{
+        Scripted{
+            reply,
+            queue:Mutex::new(VecDeque::new()),
+        }
+    }
+
+    fn queue(&self,report:[u8;REPORT_SIZE]){
+        self.queue.lock().unwrap().push_back(report);
+    }
+}
+
+impl<F:Fn(&Frame) -> Vec<[u8;REPORT_SIZE]>> Transport for Scripted<F>{
+    fn write(&self,report:&[u8;REPORT_SIZE]) -> Result<(),OpenDP100Error>{
+        let mut request = Frame::empty();
+        deserialize_out_frame(report,&mut request).unwrap();
+        self.queue.lock().unwrap().extend((self.reply)(&request));
+        Ok(())
+    }
+
+    fn read(&self,report:&mut [u8;REPORT_SIZE],_timeout_ms:i32) -> Result<usize,OpenDP100Error>{
+        match self.queue.lock().unwrap().pop_front(){
+            Some(reply) => {
+                *report = reply;
+                Ok(REPORT_SIZE)
+            }
+            None => Ok(0)
+        }
+    }
+}
+
+// BASIC_INFO reply with `vin` in mV
+fn basic_info(serial_num:u8,vin:u16) -> [u8;REPORT_SIZE]{
+    let mut data = [0u8;16];
+    data[0..2].copy_from_slice(&vin.to_le_bytes());
+    let mut frame = Frame::new(OpCode::BasicInfo,&data).unwrap();
+    frame.serial_num = serial_num;
+    let mut buffer = [0u8;REPORT_SIZE];
+    serialize_in_frame(&frame,&mut buffer);
+    buffer
+}
+
+#[test]
+fn stale_reply_in_front_is_dropped(){
+    let device = OpenDP100::with_transport(Scripted::new(|request| vec![
+        basic_info(request.serial_num.wrapping_add(100),1000),
+        basic_info(request.serial_num,20000),
+    ]));
+    assert_eq!(device.basic_info().unwrap().vin,Millivolts(20000));
+}
+
+#[test]
+fn stale_reply_to_another_op_code_is_not_an_error(){
+    let device = OpenDP100::with_transport(Scripted::new(|request| {
+        let mut stale = Frame::new(OpCode::SystemInfo,&[4,0x04,0x29,0x20,0x03,2]).unwrap();
+        stale.serial_num = request.serial_num.wrapping_add(1);
+        let mut buffer = [0u8;REPORT_SIZE];
+        serialize_in_frame(&stale,&mut buffer);
+        vec![buffer,basic_info(request.serial_num,20000)]
+    }));
+    assert_eq!(device.basic_info().unwrap().vin,Millivolts(20000));
+}
+
+#[test]
+fn duplicated_reply_does_not_answer_the_next_request(){
+    // every reply comes twice, the copy must not be taken for the next answer
+    let device = OpenDP100::with_transport(Scripted::new(|request| {
+        let vin = 1000 * request.serial_num as u16;
+        vec![basic_info(request.serial_num,vin),basic_info(request.serial_num,vin)]
+    }));
+    assert_eq!(device.basic_info().unwrap().vin,Millivolts(1000));
+    assert_eq!(device.basic_info().unwrap().vin,Millivolts(2000));
+}
+
+#[test]
+fn flush_drops_what_is_queued_before_the_request(){
+    let device = OpenDP100::with_transport(Scripted::new(|request| vec![basic_info(request.serial_num,20000)]));
+    // left over from an earlier session and carrying the serial the next
+    // request will get, only the flush keeps it from being taken
+    device.transport().queue(basic_info(1,1000));
+    device.transport().queue(basic_info(1,1000));
+    assert_eq!(device.basic_info().unwrap().vin,Millivolts(20000));
+}
+
+#[test]
+fn unnumbered_reply_is_accepted_by_default(){
+    let device = OpenDP100::with_transport(Scripted::new(|_| vec![basic_info(0,20000)]));
+    assert_eq!(device.basic_info().unwrap().vin,Millivolts(20000));
+}
+
+#[test]
+fn unnumbered_reply_is_rejected_when_disabled(){
+    let mut device = OpenDP100::with_transport(Scripted::new(|_| vec![basic_info(0,20000)]));
+    device.set_config(SessionConfig{
+        accept_unnumbered:false,
+        ..SessionConfig::default()
+    });
+    assert!(matches!(device.basic_info(),Err(OpenDP100Error::Timeout{..})));
+}
+
+#[test]
+fn serial_wraps_past_255(){
+    // 0 is skipped on the way round, the simulator echoes every serial
+    let device = OpenDP100::with_transport(Simulator::new());
+    for _ in 0..300{
+        device.basic_info().unwrap();
+    }
+}
@@ -0,0 +1,177 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use open_dp100::{serialize_out_frame, Frame, Load, Milliamps, Millivolts, OpCode, OpenDP100, OpenDP100Error, OutMode, OutputState, Simulator, Transport, WorkState, REPORT_SIZE};
+use open_dp100::simulator::PRESET_COUNT;
+
+fn simulated() -> OpenDP100<Simulator>{
+    OpenDP100::with_transport(Simulator::new())
+}
+
+#[test]
+fn ten_presets_by_index(){
+    let device = simulated();
+    for idx in 0..PRESET_COUNT{
+        assert_eq!(device.basic_set(idx).unwrap().index as usize,idx);
+    }
+    // there is no 11th preset, it is not even asked for
+    assert!(matches!(device.basic_set(PRESET_COUNT),Err(OpenDP100Error::InvalidParam(_))));
+}
+
+#[test]
+fn missing_preset_is_not_answered(){
+    let simulator = Simulator::new();
+    let mut report = [0u8;REPORT_SIZE];
+    serialize_out_frame(&Frame::new(OpCode::BasicSet,&[PRESET_COUNT as u8]).unwrap(),&mut report);
+    simulator.write(&report).unwrap();
+    assert_eq!(simulator.read(&mut report,0).unwrap(),0);
+}
+
+#[test]
+fn read_waits_for_its_timeout(){
+    let simulator = Simulator::new();
+    let mut report = [0u8;REPORT_SIZE];
+    let start = Instant::now();
+    assert_eq!(simulator.read(&mut report,50).unwrap(),0);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn read_wakes_up_for_a_reply(){
+    let simulator = Simulator::new();
+    let mut request = [0u8;REPORT_SIZE];
+    serialize_out_frame(&Frame::new(OpCode::SystemInfo,&[]).unwrap(),&mut request);
+    let start = Instant::now();
+    let size = thread::scope(|scope| {
+        scope.spawn(|| {
+            thread::sleep(Duration::from_millis(20));
+            simulator.write(&request).unwrap();
+        });
+        let mut report = [0u8;REPORT_SIZE];
+        simulator.read(&mut report,5000).unwrap()
+    });
+    assert_eq!(size,REPORT_SIZE);
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+fn modify_flag_changes_a_preset_without_activating_it(){
+    let device = simulated();
+    let mut set = device.basic_set(3).unwrap();
+    set.vo_set = Millivolts(12000);
+    // 0x20
+    device.update_basic_set(&set,false).unwrap();
+
+    assert_eq!(device.basic_set(3).unwrap().vo_set,Millivolts(12000));
+    assert_eq!(device.transport().state().unwrap().active,0);
+    assert_eq!(device.current_basic_set().unwrap().index,0);
+}
+
+#[test]
+fn unknown_output_state_is_refused(){
+    let device = simulated();
+    let mut set = device.basic_set(3).unwrap();
+    set.state = OutputState::Unknown(7);
+    assert!(matches!(device.update_basic_set(&set,false),Err(OpenDP100Error::DeviceFailure{..})));
+    assert_eq!(device.basic_set(3).unwrap().state,OutputState::Off);
+}
+
+#[test]
+fn activate_flag_switches_the_active_preset(){
+    let device = simulated();
+    // 0xa0
+    device.switch_config(4).unwrap();
+    assert_eq!(device.transport().state().unwrap().active,4);
+    // 0x80 queries the active preset
+    assert_eq!(device.current_basic_set().unwrap().index,4);
+}
+
+#[test]
+fn preset_out_of_range_is_refused(){
+    let device = simulated();
+    let mut set = device.basic_set(0).unwrap();
+    set.index = 12;
+    assert!(matches!(device.update_basic_set(&set,false),Err(OpenDP100Error::DeviceFailure{..})));
+}
+
+#[test]
+fn open_output_is_cv_without_current(){
+    let device = simulated();
+    device.set_output_on(OutputState::On).unwrap();
+    let info = device.basic_info().unwrap();
+    assert_eq!((info.vout,info.iout,info.out_mode),(Millivolts(5000),Milliamps(0),OutMode::Cv));
+}
+
+#[test]
+fn short_trips_ocp(){
+    let device = simulated();
+    device.transport().set_load(Load::Short).unwrap();
+    device.set_output_on(OutputState::On).unwrap();
+
+    let info = device.basic_info().unwrap();
+    assert_eq!((info.out_mode,info.work_st),(OutMode::Off,WorkState::Ocp));
+    assert_eq!(device.current_basic_set().unwrap().state,OutputState::Off);
+
+    // with the short gone, switching on again clears the trip
+    device.transport().set_load(Load::Open).unwrap();
+    device.set_output_on(OutputState::On).unwrap();
+    assert_eq!(device.basic_info().unwrap().work_st,WorkState::Normal);
+}
+
+#[test]
+fn resistor_switches_between_cv_and_cc(){
+    let device = simulated();
+    device.set_output_on(OutputState::On).unwrap();
+
+    // 5 V / 100 Ω = 50 mA, below the 1 A limit
+    device.transport().set_load(Load::Resistor(100.0)).unwrap();
+    let info = device.basic_info().unwrap();
+    assert_eq!((info.vout,info.iout,info.out_mode),(Millivolts(5000),Milliamps(50),OutMode::Cv));
+
+    // 5 V / 1 Ω would be 5 A, the current limit pulls the voltage down
+    device.transport().set_load(Load::Resistor(1.0)).unwrap();
+    let info = device.basic_info().unwrap();
+    assert_eq!((info.vout,info.iout,info.out_mode),(Millivolts(1000),Milliamps(1000),OutMode::Cc));
+    assert_eq!(info.work_st,WorkState::Normal);
+}
+
+#[test]
+fn heavy_load_trips_opp(){
+    let device = simulated();
+    {
+        let mut state = device.transport().state().unwrap();
+        let preset = &mut state.presets[0];
+        preset.vo_set = Millivolts(19000);
+        preset.io_set = Milliamps(10000);
+        preset.ocp_set = Milliamps(10500);
+    }
+    // 19 V / 2 Ω = 9.5 A, 180.5 W against a 105 W limit
+    device.transport().set_load(Load::Resistor(2.0)).unwrap();
+    device.set_output_on(OutputState::On).unwrap();
+
+    let info = device.basic_info().unwrap();
+    assert_eq!((info.out_mode,info.work_st),(OutMode::Off,WorkState::Opp));
+}
+
+#[test]
+fn output_is_capped_below_vin(){
+    let device = simulated();
+    let mut set = device.basic_set(0).unwrap();
+    set.vo_set = Millivolts(30000);
+    set.state = OutputState::On;
+    device.update_basic_set(&set,false).unwrap();
+
+    let info = device.basic_info().unwrap();
+    assert_eq!(info.vo_max,Millivolts(19000));
+    assert_eq!(info.vout,Millivolts(19000));
+}
+
+#[test]
+fn poisoned_state_is_a_transport_error(){
+    let device = simulated();
+    let _ = std::thread::scope(|s| s.spawn(|| {
+        let _state = device.transport().state().unwrap();
+        panic!("poison the simulator");
+    }).join());
+    assert!(matches!(device.basic_info(),Err(OpenDP100Error::Transport{..})));
+}
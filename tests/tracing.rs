@@ -0,0 +1,69 @@
+#![cfg(feature = "tracing")]
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use open_dp100::{OpenDP100, Simulator};
+
+// keeps every field recorded on spans and events as "name=value"
+#[derive(Default,Clone)]
+struct Collect{
+    fields:Arc<Mutex<Vec<String>>>,
+    next_id:Arc<AtomicU64>,
+}
+
+impl Visit for Collect{
+    fn record_debug(&mut self,field:&Field,value:&dyn Debug){
+        self.fields.lock().unwrap().push(format!("{}={:?}",field.name(),value));
+    }
+}
+
+impl Subscriber for Collect{
+    fn enabled(&self,_metadata:&Metadata<'_>) -> bool{
+        true
+    }
+
+    fn new_span(&self,span:&Attributes<'_>) -> Id{
+        span.record(&mut self.clone());
+        Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    fn record(&self,_span:&Id,values:&Record<'_>){
+        values.record(&mut self.clone());
+    }
+
+    fn record_follows_from(&self,_span:&Id,_follows:&Id){}
+
+    fn event(&self,event:&Event<'_>){
+        event.record(&mut self.clone());
+    }
+
+    fn enter(&self,_span:&Id){}
+
+    fn exit(&self,_span:&Id){}
+}
+
+#[test]
+fn session_span_records_frames(){
+    let collect = Collect::default();
+    let device = OpenDP100::with_transport(Simulator::new());
+    tracing::subscriber::with_default(collect.clone(), || {
+        device.sys_info().unwrap();
+    });
+
+    let fields = collect.fields.lock().unwrap().clone();
+    let has = |prefix:&str| fields.iter().any(|f| f.starts_with(prefix));
+    assert!(has("op_code=SystemInfo"),"{:?}",fields);
+    assert!(has("serial=1"));
+    assert!(has("request=fb400100"));
+    assert!(has("response=fa400106"));
+    assert!(has("retries=0"));
+    assert!(has("result=\"ok\""));
+    assert!(has("latency_us="));
+    assert!(has("reply=SystemInfo"));
+}
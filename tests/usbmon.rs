@@ -0,0 +1,131 @@
+use open_dp100::usbmon::{dp100_reports, read_packets, UsbDirection};
+
+// usbmon packet header (48 bytes, or 64 when mmapped) followed by data
+fn usbmon(event:u8,xfer:u8,endpoint:u8,device:u8,ts:u32,data:&[u8],mmapped:bool) -> Vec<u8>{
+    let mut p = vec![0u8;if mmapped { 64 } else { 48 }];
+    p[8] = event;
+    p[9] = xfer;
+    p[10] = endpoint;
+    p[11] = device;
+    p[12..14].copy_from_slice(&1u16.to_le_bytes());
+    p[16..20].copy_from_slice(&ts.to_le_bytes());
+    p[32..36].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    p[36..40].copy_from_slice(&(data.len() as u32).to_le_bytes());
+    p.extend_from_slice(data);
+    p
+}
+
+fn pcap(link_type:u32,packets:&[Vec<u8>]) -> Vec<u8>{
+    let mut f = Vec::new();
+    f.extend_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+    f.extend_from_slice(&[2,0,4,0]);
+    f.extend_from_slice(&[0;8]);
+    f.extend_from_slice(&65535u32.to_le_bytes());
+    f.extend_from_slice(&link_type.to_le_bytes());
+    for p in packets{
+        f.extend_from_slice(&[0;8]);
+        f.extend_from_slice(&(p.len() as u32).to_le_bytes());
+        f.extend_from_slice(&(p.len() as u32).to_le_bytes());
+        f.extend_from_slice(p);
+    }
+    f
+}
+
+fn block(block_type:u32,body:&[u8]) -> Vec<u8>{
+    let mut body = body.to_vec();
+    while !body.len().is_multiple_of(4){
+        body.push(0);
+    }
+    let len = (body.len() + 12) as u32;
+    let mut b = Vec::new();
+    b.extend_from_slice(&block_type.to_le_bytes());
+    b.extend_from_slice(&len.to_le_bytes());
+    b.extend_from_slice(&body);
+    b.extend_from_slice(&len.to_le_bytes());
+    b
+}
+
+fn pcapng(link_type:u16,packets:&[Vec<u8>]) -> Vec<u8>{
+    let mut shb = Vec::new();
+    shb.extend_from_slice(&0x1a2b3c4du32.to_le_bytes());
+    shb.extend_from_slice(&[1,0,0,0]);
+    shb.extend_from_slice(&[0xff;8]);
+    let mut idb = Vec::new();
+    idb.extend_from_slice(&link_type.to_le_bytes());
+    idb.extend_from_slice(&[0,0]);
+    idb.extend_from_slice(&0u32.to_le_bytes());
+
+    let mut f = block(0x0a0d0d0a,&shb);
+    f.extend(block(1,&idb));
+    for p in packets{
+        let mut epb = Vec::new();
+        epb.extend_from_slice(&0u32.to_le_bytes());
+        epb.extend_from_slice(&[0;8]);
+        epb.extend_from_slice(&(p.len() as u32).to_le_bytes());
+        epb.extend_from_slice(&(p.len() as u32).to_le_bytes());
+        epb.extend_from_slice(p);
+        f.extend(block(6,&epb));
+    }
+    f
+}
+
+fn report(header:u8) -> Vec<u8>{
+    let mut r = vec![0u8;64];
+    r[0..6].copy_from_slice(&[header,0x30,0x01,0x00,0x30,0x9f]);
+    r
+}
+
+fn device_descriptor(vid:u16,pid:u16) -> Vec<u8>{
+    let mut d = vec![0u8;18];
+    d[0] = 18;
+    d[1] = 1;
+    d[8..10].copy_from_slice(&vid.to_le_bytes());
+    d[10..12].copy_from_slice(&pid.to_le_bytes());
+    d
+}
+
+#[test]
+fn pcap_reports_are_filtered_by_descriptor(){
+    let file = pcap(189,&[
+        usbmon(b'C',2,0x80,5,1,&device_descriptor(0x2e3c,0xaf01),false),
+        usbmon(b'C',2,0x80,6,1,&device_descriptor(0x046d,0xc52b),false),
+        // OUT data is in the submit, the completion carries none
+        usbmon(b'S',1,0x01,5,2,&report(0xfb),false),
+        usbmon(b'C',1,0x01,5,2,&[],false),
+        // IN data is in the completion
+        usbmon(b'S',1,0x81,5,3,&[],false),
+        usbmon(b'C',1,0x81,5,3,&report(0xfa),false),
+        // another device that happens to look alike
+        usbmon(b'C',1,0x81,6,4,&report(0xfa),false),
+    ]);
+
+    let packets = read_packets(&file[..]).unwrap();
+    assert_eq!(packets.len(),7);
+    let reports = dp100_reports(&packets);
+    assert_eq!(reports.len(),2);
+    assert_eq!(reports[0].direction,UsbDirection::Out);
+    assert_eq!(reports[0].data,report(0xfb));
+    assert_eq!(reports[1].direction,UsbDirection::In);
+    assert_eq!(reports[1].device,5);
+    assert_eq!(reports[1].timestamp,3.0);
+}
+
+#[test]
+fn pcapng_without_descriptors_falls_back_to_headers(){
+    let file = pcapng(220,&[
+        usbmon(b'S',1,0x01,7,1,&report(0xfb),true),
+        usbmon(b'C',1,0x81,7,1,&report(0xfa),true),
+        usbmon(b'C',1,0x81,8,1,&[1,2,3],true),
+    ]);
+
+    let reports = dp100_reports(&read_packets(&file[..]).unwrap());
+    assert_eq!(reports.len(),2);
+    assert_eq!(reports[0].report()[0],0xfb);
+    assert_eq!(reports[1].report()[0],0xfa);
+}
+
+#[test]
+fn not_a_capture_is_an_error(){
+    assert!(read_packets(&b"# open_dp100 capture\n"[..]).is_err());
+    assert!(read_packets(&pcap(1,&[])[..]).is_err());
+}